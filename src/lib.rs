@@ -2,11 +2,12 @@
 mod tests;
 
 use std::{
-    iter::Sum,
-    ops::{Add, Sub},
+    cmp::Ordering,
+    iter::{FusedIterator, Sum},
+    ops::{Add, Range, Sub},
 };
 
-use itertools::Itertools;
+use instrument::Observer;
 
 pub fn linear_search<T>(key: &T, items: &[T]) -> Option<usize>
 where
@@ -23,14 +24,25 @@ where
 pub fn binary_search<T>(key: &T, items: &[T]) -> Option<usize>
 where
     T: PartialOrd,
+{
+    binary_search_observed(key, items, &mut ())
+}
+
+pub(crate) fn binary_search_observed<T, O>(key: &T, items: &[T], observer: &mut O) -> Option<usize>
+where
+    T: PartialOrd,
+    O: Observer<T>,
 {
     let mut start = 0;
     let mut end = items.len();
     while start < end {
         let check_idx = (start + end) / 2;
+        observer.probe(check_idx);
         if *key == items[check_idx] {
             return Some(check_idx);
-        } else if *key < items[check_idx] {
+        }
+        observer.probe(check_idx);
+        if *key < items[check_idx] {
             end = check_idx;
         } else {
             start = check_idx + 1;
@@ -39,6 +51,212 @@ where
     None
 }
 
+pub fn exponential_search<T>(key: &T, items: &[T]) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    exponential_search_unbounded(&key, |idx| items.get(idx))
+}
+
+pub fn exponential_search_unbounded<T, F>(key: &T, mut item_at: F) -> Option<usize>
+where
+    T: PartialOrd,
+    F: FnMut(usize) -> Option<T>,
+{
+    // an index past the end of the source (None) is treated as greater than every key
+    let is_before_key = |item: &Option<T>| matches!(item, Some(item) if *item < *key);
+    let first = item_at(0);
+    if !is_before_key(&first) {
+        return matches!(first, Some(item) if item == *key).then_some(0);
+    }
+    let mut bound = 1;
+    while is_before_key(&item_at(bound)) {
+        bound *= 2;
+    }
+    let mut start = bound / 2 + 1;
+    let mut end = bound + 1;
+    while start < end {
+        let check_idx = start + (end - start) / 2;
+        match item_at(check_idx) {
+            Some(item) if item == *key => return Some(check_idx),
+            Some(item) if item < *key => start = check_idx + 1,
+            _ => end = check_idx,
+        }
+    }
+    None
+}
+
+pub fn interpolation_search<T>(key: &T, items: &[T]) -> Option<usize>
+where
+    T: Copy + PartialOrd + Into<f64>,
+{
+    if items.is_empty() {
+        return None;
+    }
+    let mut start = 0;
+    let mut end = items.len() - 1;
+    while start <= end && *key >= items[start] && *key <= items[end] {
+        let (low, high) = (items[start].into(), items[end].into());
+        if high == low {
+            return (items[start] == *key).then_some(start);
+        }
+        let fraction = ((*key).into() - low) / (high - low);
+        let check_idx = start + (fraction * (end - start) as f64) as usize;
+        if items[check_idx] == *key {
+            return Some(check_idx);
+        } else if items[check_idx] < *key {
+            start = check_idx + 1;
+        } else if check_idx == 0 {
+            break;
+        } else {
+            end = check_idx - 1;
+        }
+    }
+    None
+}
+
+pub fn jump_search<T>(key: &T, items: &[T]) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    let step = ((items.len() as f64).sqrt() as usize).max(1);
+    let mut block_start = 0;
+    while block_start < items.len() {
+        let block_end = (block_start + step).min(items.len());
+        if items[block_end - 1] >= *key {
+            return (block_start..block_end).find(|idx| items[*idx] == *key);
+        }
+        block_start = block_end;
+    }
+    None
+}
+
+pub fn fibonacci_search<T>(key: &T, items: &[T]) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    let (mut fib_2, mut fib_1) = (0, 1);
+    let mut fib = fib_2 + fib_1;
+    while fib < items.len() {
+        fib_2 = fib_1;
+        fib_1 = fib;
+        fib = fib_2 + fib_1;
+    }
+    // offset is the count of items already eliminated from the front
+    let mut offset = 0;
+    while fib > 1 {
+        let check_idx = (offset + fib_2).min(items.len()) - 1;
+        if items[check_idx] < *key {
+            fib = fib_1;
+            fib_1 = fib_2;
+            fib_2 = fib - fib_1;
+            offset = check_idx + 1;
+        } else if items[check_idx] > *key {
+            fib = fib_2;
+            fib_1 -= fib_2;
+            fib_2 = fib - fib_1;
+        } else {
+            return Some(check_idx);
+        }
+    }
+    (fib_1 == 1 && offset < items.len() && items[offset] == *key).then_some(offset)
+}
+
+// Items that cannot be compared with the key sort after it, so a key that cannot be compared with
+// itself, such as NaN, finds the empty range at the front
+pub fn lower_bound<T>(key: &T, items: &[T]) -> usize
+where
+    T: PartialOrd,
+{
+    lower_bound_by(items, |item| {
+        item.partial_cmp(key).unwrap_or(Ordering::Greater)
+    })
+}
+
+pub fn upper_bound<T>(key: &T, items: &[T]) -> usize
+where
+    T: PartialOrd,
+{
+    upper_bound_by(items, |item| {
+        item.partial_cmp(key).unwrap_or(Ordering::Greater)
+    })
+}
+
+pub fn equal_range<T>(key: &T, items: &[T]) -> Range<usize>
+where
+    T: PartialOrd,
+{
+    equal_range_by(items, |item| {
+        item.partial_cmp(key).unwrap_or(Ordering::Greater)
+    })
+}
+
+pub fn lower_bound_by<T, F>(items: &[T], mut compare: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut start = 0;
+    let mut end = items.len();
+    while start < end {
+        let check_idx = start + (end - start) / 2;
+        if compare(&items[check_idx]) == Ordering::Less {
+            start = check_idx + 1;
+        } else {
+            end = check_idx;
+        }
+    }
+    start
+}
+
+pub fn upper_bound_by<T, F>(items: &[T], mut compare: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut start = 0;
+    let mut end = items.len();
+    while start < end {
+        let check_idx = start + (end - start) / 2;
+        if compare(&items[check_idx]) == Ordering::Greater {
+            end = check_idx;
+        } else {
+            start = check_idx + 1;
+        }
+    }
+    start
+}
+
+pub fn equal_range_by<T, F>(items: &[T], mut compare: F) -> Range<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let start = lower_bound_by(items, &mut compare);
+    start..start + upper_bound_by(&items[start..], compare)
+}
+
+pub fn lower_bound_by_key<T, K, F>(key: &K, items: &[T], mut key_of: F) -> usize
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    lower_bound_by(items, |item| key_of(item).cmp(key))
+}
+
+pub fn upper_bound_by_key<T, K, F>(key: &K, items: &[T], mut key_of: F) -> usize
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    upper_bound_by(items, |item| key_of(item).cmp(key))
+}
+
+pub fn equal_range_by_key<T, K, F>(key: &K, items: &[T], mut key_of: F) -> Range<usize>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    equal_range_by(items, |item| key_of(item).cmp(key))
+}
+
 #[allow(clippy::manual_swap)]
 pub fn reverse<T>(items: &mut [T])
 where
@@ -59,48 +277,267 @@ pub fn pairs<T>(items: &[T]) -> Vec<(T, T)>
 where
     T: Copy,
 {
-    let mut pairs = Vec::new();
-    for (i, item1) in items.iter().enumerate() {
-        for item2 in items.iter().skip(i + 1) {
-            pairs.push((*item1, *item2));
-        }
-    }
-    pairs
+    pairs_iter(items).map(|(a, b)| (*a, *b)).collect()
 }
 
 pub fn pair_indices<T>(items: &[T]) -> Vec<(usize, usize)>
 where
     T: Copy,
 {
-    let mut pairs = Vec::new();
-    for (i, _) in items.iter().enumerate() {
-        for (j, _) in items.iter().enumerate().skip(i + 1) {
-            pairs.push((i, j));
-        }
-    }
-    pairs
+    pair_indices_iter(items.len()).collect()
 }
 
 pub fn subarrays<T>(items: &[T]) -> Vec<&[T]> {
-    let mut subarrays = Vec::new();
-    for i in 0..items.len() {
-        for j in i..items.len() {
-            subarrays.push(&items[i..=j])
-        }
-    }
-    subarrays
+    subarrays_iter(items).collect()
 }
 
 pub fn subarrays_sums<T>(items: &[T]) -> Vec<T>
 where
     T: Copy + Sum,
 {
-    subarrays(items)
-        .iter()
+    subarrays_iter(items)
         .map(|a| a.iter().copied().sum())
         .collect()
 }
 
+pub fn pairs_iter<T>(items: &[T]) -> PairIter<'_, T> {
+    PairIter {
+        items,
+        indices: pair_indices_iter(items.len()),
+    }
+}
+
+pub fn pair_indices_iter(len: usize) -> PairIndexIter {
+    PairIndexIter { len, i: 0, j: 1 }
+}
+
+pub fn subarrays_iter<T>(items: &[T]) -> SubarrayIter<'_, T> {
+    SubarrayIter {
+        items,
+        start: 0,
+        end: 1,
+    }
+}
+
+pub fn combinations_iter<T>(items: &[T], k: usize) -> CombinationIter<'_, T> {
+    // C(n, k) built up as C(n - k + i, i), each step of which divides exactly; a count that
+    // fits in usize never overflows u128 on the way
+    let remaining = if k > items.len() {
+        Some(0)
+    } else {
+        (1..=k as u128)
+            .try_fold(1_u128, |count, i| {
+                Some(count.checked_mul(items.len() as u128 - k as u128 + i)? / i)
+            })
+            .and_then(|count| usize::try_from(count).ok())
+    };
+    CombinationIter {
+        items,
+        indices: (0..k).collect(),
+        done: k > items.len(),
+        remaining,
+    }
+}
+
+pub fn windows_iter<T>(items: &[T], size: usize) -> WindowIter<'_, T> {
+    WindowIter {
+        items,
+        size,
+        start: 0,
+        end: if size == 0 {
+            0
+        } else {
+            (items.len() + 1).saturating_sub(size)
+        },
+    }
+}
+
+/// Yields (i, j) for every i < j < len, in the same order as pair_indices.
+///
+/// Like CombinationIter this is not an ExactSizeIterator: len * (len - 1) / 2 outgrows usize
+/// long before len does, so size_hint is exact only while the count fits.
+#[derive(Debug, Clone)]
+pub struct PairIndexIter {
+    len: usize,
+    i: usize,
+    j: usize,
+}
+
+impl Iterator for PairIndexIter {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.j >= self.len {
+            return None;
+        }
+        let pair = (self.i, self.j);
+        self.j += 1;
+        if self.j == self.len {
+            self.i += 1;
+            self.j = self.i + 1;
+        }
+        Some(pair)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.j >= self.len {
+            return (0, Some(0));
+        }
+        // the rest of row i, then every pair among the indices after it; squared in u128,
+        // which cannot overflow for a usize
+        let after = (self.len - self.i - 1) as u128;
+        let remaining = (self.len - self.j) as u128 + after * (after - 1) / 2;
+        remaining_hint(remaining)
+    }
+}
+
+impl FusedIterator for PairIndexIter {}
+
+fn remaining_hint(remaining: u128) -> (usize, Option<usize>) {
+    match usize::try_from(remaining) {
+        Ok(remaining) => (remaining, Some(remaining)),
+        Err(_) => (usize::MAX, None),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PairIter<'a, T> {
+    items: &'a [T],
+    indices: PairIndexIter,
+}
+
+impl<'a, T> Iterator for PairIter<'a, T> {
+    type Item = (&'a T, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, j) = self.indices.next()?;
+        Some((&self.items[i], &self.items[j]))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<T> FusedIterator for PairIter<'_, T> {}
+
+/// Yields items[start..end] for every non-empty range, in the same order as subarrays.
+///
+/// Not an ExactSizeIterator for the same reason as PairIndexIter.
+#[derive(Debug, Clone)]
+pub struct SubarrayIter<'a, T> {
+    items: &'a [T],
+    start: usize,
+    end: usize,
+}
+
+impl<'a, T> Iterator for SubarrayIter<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.end > self.items.len() {
+            return None;
+        }
+        let subarray = &self.items[self.start..self.end];
+        self.end += 1;
+        if self.end > self.items.len() {
+            self.start += 1;
+            self.end = self.start + 1;
+        }
+        Some(subarray)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.items.len();
+        if self.end > len {
+            return (0, Some(0));
+        }
+        let after = (len - self.start - 1) as u128;
+        remaining_hint((len - self.end) as u128 + 1 + after * (after + 1) / 2)
+    }
+}
+
+impl<T> FusedIterator for SubarrayIter<'_, T> {}
+
+/// Yields every k-element selection in lexicographic order of positions.
+///
+/// Not an ExactSizeIterator, since C(n, k) soon outgrows usize; size_hint is exact whenever
+/// the count fits.
+#[derive(Debug, Clone)]
+pub struct CombinationIter<'a, T> {
+    items: &'a [T],
+    indices: Vec<usize>,
+    done: bool,
+    remaining: Option<usize>,
+}
+
+impl<'a, T> Iterator for CombinationIter<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        self.remaining = self.remaining.map(|remaining| remaining - 1);
+        let combination = self.indices.iter().map(|&idx| &self.items[idx]).collect();
+        // bump the rightmost index that still has room, and reset the ones after it; when
+        // none has room this was the last combination
+        let (n, k) = (self.items.len(), self.indices.len());
+        match (0..k).rev().find(|&pos| self.indices[pos] < n - k + pos) {
+            Some(pos) => {
+                self.indices[pos] += 1;
+                for next in pos + 1..k {
+                    self.indices[next] = self.indices[next - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(combination)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (self.done, self.remaining) {
+            (true, _) => (0, Some(0)),
+            (false, Some(remaining)) => (remaining, Some(remaining)),
+            (false, None) => (usize::MAX, None),
+        }
+    }
+}
+impl<T> FusedIterator for CombinationIter<'_, T> {}
+
+#[derive(Debug, Clone)]
+pub struct WindowIter<'a, T> {
+    items: &'a [T],
+    size: usize,
+    start: usize,
+    end: usize,
+}
+
+impl<'a, T> Iterator for WindowIter<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(&self.items[self.start - 1..self.start - 1 + self.size])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.start;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for WindowIter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(&self.items[self.end..self.end + self.size])
+    }
+}
+
+impl<T> ExactSizeIterator for WindowIter<'_, T> {}
+impl<T> FusedIterator for WindowIter<'_, T> {}
+
 pub fn subarrays_max_sum<T>(items: &[T]) -> Option<T>
 where
     T: Copy + Sum + Ord,
@@ -197,6 +634,21 @@ where
 pub fn bubble_sort<T>(items: &mut [T])
 where
     T: Copy + Ord,
+{
+    bubble_sort_by(items, T::cmp)
+}
+
+pub fn bubble_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    bubble_sort_observed(items, &mut compare, &mut ())
+}
+
+pub(crate) fn bubble_sort_observed<T, F, O>(items: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
 {
     if items.len() <= 1 {
         return;
@@ -204,8 +656,10 @@ where
     for i in 0..items.len() - 1 {
         let mut swapped = false;
         for j in 0..items.len() - 1 - i {
-            if items[j] > items[j + 1] {
+            observer.compare(j, j + 1);
+            if compare(&items[j], &items[j + 1]) == Ordering::Greater {
                 items.swap(j, j + 1);
+                observer.swap(j, j + 1);
                 swapped = true;
             }
         }
@@ -215,19 +669,46 @@ where
     }
 }
 
+pub fn bubble_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bubble_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
 pub fn insertion_sort<T>(items: &mut [T])
 where
     T: Copy + Ord,
+{
+    insertion_sort_by(items, T::cmp)
+}
+
+pub fn insertion_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    insertion_sort_observed(items, 0, &mut compare, &mut ())
+}
+
+pub(crate) fn insertion_sort_observed<T, F, O>(
+    items: &mut [T],
+    offset: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
 {
     if items.len() <= 1 {
         return;
     }
     for i in 1..items.len() {
-        let tmp = items[i];
         for j in (0..i).rev() {
-            if items[j] > tmp {
-                items[j + 1] = items[j];
-                items[j] = tmp;
+            observer.compare(offset + j, offset + j + 1);
+            if compare(&items[j], &items[j + 1]) == Ordering::Greater {
+                items.swap(j, j + 1);
+                observer.swap(offset + j, offset + j + 1);
             } else {
                 break;
             }
@@ -235,9 +716,32 @@ where
     }
 }
 
+pub fn insertion_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    insertion_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
 pub fn selection_sort<T>(items: &mut [T])
 where
     T: Copy + Ord,
+{
+    selection_sort_by(items, T::cmp)
+}
+
+pub fn selection_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    selection_sort_observed(items, &mut compare, &mut ())
+}
+
+pub(crate) fn selection_sort_observed<T, F, O>(items: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
 {
     if items.len() <= 1 {
         return;
@@ -245,39 +749,452 @@ where
     for i in 0..items.len() - 1 {
         let mut min_idx = i;
         for j in (i + 1)..items.len() {
-            if items[min_idx] > items[j] {
+            observer.compare(min_idx, j);
+            if compare(&items[min_idx], &items[j]) == Ordering::Greater {
                 min_idx = j;
             }
         }
-        items.swap(min_idx, i);
+        if min_idx != i {
+            items.swap(min_idx, i);
+            observer.swap(i, min_idx);
+        }
     }
 }
 
-pub fn counting_sort<T>(items: &mut [T])
+pub fn selection_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
 where
-    T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T> + Into<usize> + From<usize>,
+    K: Ord,
+    F: FnMut(&T) -> K,
 {
-    if items.len() <= 1 {
-        return;
+    selection_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
+pub fn heap_sort<T>(items: &mut [T])
+where
+    T: Ord,
+{
+    heap_sort_by(items, T::cmp)
+}
+
+pub fn heap_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_sort_observed(items, 0, &mut compare, &mut ())
+}
+
+pub(crate) fn heap_sort_observed<T, F, O>(
+    items: &mut [T],
+    offset: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
+{
+    for start in (0..items.len() / 2).rev() {
+        sift_down(items, start, offset, compare, observer);
     }
-    let (min, max) = match items.iter().minmax() {
-        itertools::MinMaxResult::MinMax(min, max) => (*min, *max),
-        _ => unreachable!(),
-    };
-    let mut counts = vec![0_usize; (max - min).into() + 1];
-    for item in items.iter() {
-        counts[(*item - min).into()] += 1;
+    for end in (1..items.len()).rev() {
+        items.swap(0, end);
+        observer.swap(offset, offset + end);
+        sift_down(&mut items[..end], 0, offset, compare, observer);
     }
-    let mut item_idx = 0;
-    for (count_idx, count) in counts.into_iter().enumerate() {
-        for _ in 0..count {
-            items[item_idx] = T::from(count_idx) + min;
-            item_idx += 1;
+}
+
+pub fn heap_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    heap_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
+fn sift_down<T, F, O>(
+    items: &mut [T],
+    mut root: usize,
+    offset: usize,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= items.len() {
+            return;
+        }
+        if child + 1 < items.len() {
+            observer.compare(offset + child, offset + child + 1);
+            if compare(&items[child], &items[child + 1]) == Ordering::Less {
+                child += 1;
+            }
+        }
+        observer.compare(offset + root, offset + child);
+        if compare(&items[root], &items[child]) != Ordering::Less {
+            return;
         }
+        items.swap(root, child);
+        observer.swap(offset + root, offset + child);
+        root = child;
     }
 }
 
-pub fn chopsticks(items: &mut [u32], delta: u32) -> u32 {
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GapSequence {
+    Shell,
+    Knuth,
+    #[default]
+    Ciura,
+    Sedgewick,
+}
+
+impl GapSequence {
+    pub fn gaps(&self, len: usize) -> Vec<usize> {
+        let mut gaps = match self {
+            GapSequence::Shell => {
+                let mut gaps = Vec::new();
+                let mut gap = len / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.reverse();
+                gaps
+            }
+            GapSequence::Knuth => {
+                let mut gaps = vec![1];
+                while gaps[gaps.len() - 1] * 3 + 1 < len {
+                    gaps.push(gaps[gaps.len() - 1] * 3 + 1);
+                }
+                gaps
+            }
+            GapSequence::Ciura => {
+                let mut gaps = vec![1, 4, 10, 23, 57, 132, 301, 701];
+                while gaps[gaps.len() - 1] < len {
+                    gaps.push(gaps[gaps.len() - 1] * 9 / 4);
+                }
+                gaps
+            }
+            GapSequence::Sedgewick => {
+                let mut gaps = vec![1];
+                let mut k = 1;
+                while gaps[gaps.len() - 1] < len {
+                    gaps.push(4_usize.pow(k) + 3 * 2_usize.pow(k - 1) + 1);
+                    k += 1;
+                }
+                gaps
+            }
+        };
+        gaps.retain(|gap| *gap < len.max(2));
+        gaps.reverse();
+        gaps
+    }
+}
+
+pub fn shell_sort<T>(items: &mut [T])
+where
+    T: Ord,
+{
+    shell_sort_by_with(items, GapSequence::default(), T::cmp)
+}
+
+pub fn shell_sort_with<T>(items: &mut [T], gaps: GapSequence)
+where
+    T: Ord,
+{
+    shell_sort_by_with(items, gaps, T::cmp)
+}
+
+pub fn shell_sort_by<T, F>(items: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shell_sort_by_with(items, GapSequence::default(), compare)
+}
+
+pub fn shell_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    shell_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
+pub fn shell_sort_by_with<T, F>(items: &mut [T], gaps: GapSequence, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    shell_sort_observed(items, gaps, &mut compare, &mut ())
+}
+
+pub(crate) fn shell_sort_observed<T, F, O>(
+    items: &mut [T],
+    gaps: GapSequence,
+    compare: &mut F,
+    observer: &mut O,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
+{
+    for gap in gaps.gaps(items.len()) {
+        for i in gap..items.len() {
+            let mut j = i;
+            while j >= gap {
+                observer.compare(j - gap, j);
+                if compare(&items[j - gap], &items[j]) != Ordering::Greater {
+                    break;
+                }
+                items.swap(j - gap, j);
+                observer.swap(j - gap, j);
+                j -= gap;
+            }
+        }
+    }
+}
+
+pub fn tim_sort<T>(items: &mut [T])
+where
+    T: Ord,
+{
+    tim_sort_by(items, T::cmp)
+}
+
+pub fn tim_sort_by<T, F>(items: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    tim_sort_observed(items, &mut compare, &mut ())
+}
+
+pub(crate) fn tim_sort_observed<T, F, O>(items: &mut [T], compare: &mut F, observer: &mut O)
+where
+    F: FnMut(&T, &T) -> Ordering,
+    O: Observer<T>,
+{
+    let len = items.len();
+    if len <= 1 || std::mem::size_of::<T>() == 0 {
+        return;
+    }
+    let min_run = tim_sort_min_run(len);
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    let mut buf = Vec::with_capacity(len / 2);
+    // each run is (start, len); adjacent runs on the stack are adjacent in items
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let mut end = start + 1;
+        let mut descends = |end: usize, items: &[T], observer: &mut O| {
+            observer.compare(end - 1, end);
+            is_less(&items[end], &items[end - 1])
+        };
+        if end < len {
+            let descending = descends(end, items, observer);
+            end += 1;
+            while end < len && descends(end, items, observer) == descending {
+                end += 1;
+            }
+            // only strictly descending runs are reversed, which keeps the sort stable
+            if descending {
+                for (a, b) in (start..end).zip((start..end).rev()).take((end - start) / 2) {
+                    items.swap(a, b);
+                    observer.swap(a, b);
+                }
+            }
+        }
+        if end - start < min_run {
+            end = (start + min_run).min(len);
+            // insertion sort only ever moves an item past one that compares Greater
+            let mut compare_run = |a: &T, b: &T| {
+                if is_less(b, a) {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            };
+            insertion_sort_observed(&mut items[start..end], start, &mut compare_run, observer);
+        }
+        runs.push((start, end - start));
+        while let Some(idx) = tim_sort_collapse(&runs, end == len) {
+            let (left_start, left_len) = runs[idx];
+            let (_, right_len) = runs[idx + 1];
+            divide_conquer::sort::merge(
+                &mut items[left_start..left_start + left_len + right_len],
+                left_start,
+                left_len,
+                &mut buf,
+                &mut is_less,
+                observer,
+            );
+            runs[idx] = (left_start, left_len + right_len);
+            runs.remove(idx + 1);
+        }
+        start = end;
+    }
+}
+
+pub fn tim_sort_by_key<T, K, F>(items: &mut [T], mut key_of: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    tim_sort_by(items, |a, b| key_of(a).cmp(&key_of(b)))
+}
+
+fn tim_sort_min_run(mut len: usize) -> usize {
+    let mut remainder = 0;
+    while len >= 64 {
+        remainder |= len & 1;
+        len >>= 1;
+    }
+    len + remainder
+}
+
+// Picks the pair of runs to merge next so that run lengths keep growing at least like the
+// Fibonacci numbers from the top of the stack down; once all input is consumed everything merges
+fn tim_sort_collapse(runs: &[(usize, usize)], finished: bool) -> Option<usize> {
+    let n = runs.len();
+    if n < 2 {
+        return None;
+    }
+    let len = |idx: usize| runs[idx].1;
+    if finished {
+        return Some(if n >= 3 && len(n - 3) < len(n - 1) {
+            n - 3
+        } else {
+            n - 2
+        });
+    }
+    if (n >= 3 && len(n - 3) <= len(n - 2) + len(n - 1))
+        || (n >= 4 && len(n - 4) <= len(n - 3) + len(n - 2))
+    {
+        Some(if len(n - 3) < len(n - 1) {
+            n - 3
+        } else {
+            n - 2
+        })
+    } else if len(n - 2) <= len(n - 1) {
+        Some(n - 2)
+    } else {
+        None
+    }
+}
+
+pub fn bucket_sort<T>(items: &mut [T])
+where
+    T: Copy + Into<f64>,
+{
+    bucket_sort_observed(items, &mut ())
+}
+
+pub(crate) fn bucket_sort_observed<T, O>(items: &mut [T], observer: &mut O)
+where
+    T: Copy + Into<f64>,
+    O: Observer<T>,
+{
+    if items.len() <= 1 {
+        return;
+    }
+    let value = |idx: usize| -> f64 { items[idx].into() };
+    let mut finite = (0..items.len()).filter(|idx| value(*idx).is_finite());
+    let (min, max) = match finite.next() {
+        None => (f64::INFINITY, f64::NEG_INFINITY),
+        Some(first) => {
+            let (mut min_idx, mut max_idx) = (first, first);
+            for idx in finite {
+                observer.compare(min_idx, idx);
+                if value(idx) < value(min_idx) {
+                    min_idx = idx;
+                    continue;
+                }
+                observer.compare(max_idx, idx);
+                if value(idx) > value(max_idx) {
+                    max_idx = idx;
+                }
+            }
+            (value(min_idx), value(max_idx))
+        }
+    };
+    let last_bucket = items.len() - 1;
+    let bucket_of = |value: f64| {
+        if value.is_nan() || value.is_infinite() {
+            if value.is_sign_negative() {
+                0
+            } else {
+                last_bucket
+            }
+        } else if max > min {
+            (((value - min) / (max - min)) * last_bucket as f64) as usize
+        } else {
+            0
+        }
+    };
+    let mut buckets = vec![Vec::new(); items.len()];
+    for item in items.iter() {
+        buckets[bucket_of((*item).into())].push(*item);
+    }
+    let mut idx = 0;
+    for bucket in buckets {
+        // each bucket is written back in place and then sorted where it lies
+        let start = idx;
+        for item in bucket {
+            items[idx] = item;
+            observer.write(idx, &item);
+            idx += 1;
+        }
+        insertion_sort_observed(
+            &mut items[start..idx],
+            start,
+            &mut |a: &T, b: &T| (*a).into().total_cmp(&(*b).into()),
+            observer,
+        );
+    }
+}
+
+pub fn counting_sort<T>(items: &mut [T])
+where
+    T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T> + Into<usize> + From<usize>,
+{
+    counting_sort_observed(items, &mut ())
+}
+
+pub(crate) fn counting_sort_observed<T, O>(items: &mut [T], observer: &mut O)
+where
+    T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T> + Into<usize> + From<usize>,
+    O: Observer<T>,
+{
+    if items.len() <= 1 {
+        return;
+    }
+    let (mut min_idx, mut max_idx) = (0, 0);
+    for idx in 1..items.len() {
+        observer.compare(min_idx, idx);
+        if items[idx] < items[min_idx] {
+            min_idx = idx;
+            continue;
+        }
+        observer.compare(max_idx, idx);
+        if items[idx] > items[max_idx] {
+            max_idx = idx;
+        }
+    }
+    let (min, max) = (items[min_idx], items[max_idx]);
+    let mut counts = vec![0_usize; (max - min).into() + 1];
+    for item in items.iter() {
+        counts[(*item - min).into()] += 1;
+    }
+    let mut item_idx = 0;
+    for (count_idx, count) in counts.into_iter().enumerate() {
+        for _ in 0..count {
+            items[item_idx] = T::from(count_idx) + min;
+            observer.write(item_idx, &items[item_idx]);
+            item_idx += 1;
+        }
+    }
+}
+
+pub fn chopsticks(items: &mut [u32], delta: u32) -> u32 {
     items.sort_unstable();
     let mut max_pairs = 0;
     let mut i = 0;
@@ -481,160 +1398,236 @@ pub mod binary_string {
 }
 
 pub mod print_2d {
-    use std::ops::{Index, IndexMut};
+    use std::iter::FusedIterator;
 
-    #[derive(Debug, Clone)]
-    pub struct Vec2d<T>
-    where
-        T: Copy + Eq,
-    {
-        storage: Vec<T>,
-        width: usize,
+    pub use crate::grid::Grid as Vec2d;
+
+    pub fn spiral_print(input: Vec2d<u32>) -> Vec<u32> {
+        spiral(&input, Rotation::Clockwise, SpiralDirection::Inward)
+            .map(|(_, value)| *value)
+            .collect()
     }
 
-    impl<T: Copy + Eq> Vec2d<T> {
-        #[allow(dead_code)]
-        pub fn new(width: usize, height: usize, initialize_with: T) -> Self {
-            Self {
-                storage: vec![initialize_with; width * height],
-                width,
-            }
-        }
-        pub fn width(&self) -> usize {
-            self.width
-        }
-        pub fn height(&self) -> usize {
-            if self.width == 0 {
-                self.storage.len()
-            } else {
-                self.storage.len() / self.width
-            }
-        }
-        pub fn size(&self) -> usize {
-            self.storage.len()
-        }
+    // a column wave taken from the right-hand edge, so down the last column first
+    pub fn reverse_wave_print(input: Vec2d<u32>) -> Vec<u32> {
+        let width = input.width();
+        wave(&input, Axis::Columns)
+            .map(|((col, row), _)| input[(width - 1 - col, row)])
+            .collect()
     }
 
-    impl<T: Copy + Eq> From<Vec<Vec<T>>> for Vec2d<T> {
-        fn from(values: Vec<Vec<T>>) -> Self {
-            let width = if values.is_empty() {
-                0
-            } else {
-                values[0].len()
-            };
-            Self {
-                storage: values.into_iter().flat_map(|v| v.into_iter()).collect(),
-                width,
-            }
-        }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Rotation {
+        Clockwise,
+        CounterClockwise,
     }
 
-    impl<T: Copy + Eq> Index<(usize, usize)> for Vec2d<T> {
-        type Output = T;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SpiralDirection {
+        // from the top-left corner in to the middle
+        Inward,
+        // from the middle out to the top-left corner
+        Outward,
+    }
 
-        fn index(&self, (col, row): (usize, usize)) -> &Self::Output {
-            assert!(col < self.width, "Column Index out-of-range");
-            assert!(
-                row < self.storage.len() / self.width,
-                "Row Index out-of-range"
-            );
-            self.storage.index(row * self.width + col)
-        }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Axis {
+        Rows,
+        Columns,
     }
 
-    impl<T: Copy + Eq> IndexMut<(usize, usize)> for Vec2d<T> {
-        fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut Self::Output {
-            assert!(col < self.width, "Column Index out-of-range");
-            assert!(
-                row < self.storage.len() / self.width,
-                "Row Index out-of-range"
-            );
-            self.storage.index_mut(row * self.width + col)
-        }
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Order {
+        Spiral(Rotation, SpiralDirection),
+        // snakes along each row (or column) in turn, reversing direction every time
+        Wave(Axis),
+        // zigzags along the lines where col + row is constant, starting at the top-left
+        Diagonal,
+        // zigzags along the lines where col - row is constant, starting at the top-right
+        AntiDiagonal,
     }
 
-    impl<T: Copy + Eq> PartialEq for Vec2d<T> {
-        fn eq(&self, other: &Self) -> bool {
-            self.storage == other.storage && self.width == other.width
+    pub fn traverse<T>(grid: &Vec2d<T>, order: Order) -> Traversal<'_, T> {
+        Traversal {
+            grid,
+            order,
+            front: 0,
+            back: grid.size(),
         }
     }
+    pub fn spiral<T>(
+        grid: &Vec2d<T>,
+        rotation: Rotation,
+        direction: SpiralDirection,
+    ) -> Traversal<'_, T> {
+        traverse(grid, Order::Spiral(rotation, direction))
+    }
+    pub fn wave<T>(grid: &Vec2d<T>, axis: Axis) -> Traversal<'_, T> {
+        traverse(grid, Order::Wave(axis))
+    }
+    pub fn diagonal_zigzag<T>(grid: &Vec2d<T>) -> Traversal<'_, T> {
+        traverse(grid, Order::Diagonal)
+    }
+    pub fn anti_diagonal_zigzag<T>(grid: &Vec2d<T>) -> Traversal<'_, T> {
+        traverse(grid, Order::AntiDiagonal)
+    }
+    // Left to right along the first row, right to left along the next, and so on; the same walk
+    // as wave(grid, Axis::Rows), under the name it usually goes by
+    pub fn boustrophedon<T>(grid: &Vec2d<T>) -> Traversal<'_, T> {
+        traverse(grid, Order::Wave(Axis::Rows))
+    }
 
-    impl<T: Copy + Eq> Eq for Vec2d<T> {}
+    // Maps each position in the traversal straight to its coordinate, so it can be walked from
+    // either end without any intermediate state
+    #[derive(Debug)]
+    pub struct Traversal<'a, T> {
+        grid: &'a Vec2d<T>,
+        order: Order,
+        front: usize,
+        back: usize,
+    }
 
-    pub fn spiral_print(input: Vec2d<u32>) -> Vec<u32> {
-        let mut col = 0;
-        let mut row = 0;
-        let mut direction = Direction::East;
-        let mut top_row = 0;
-        let mut left_column = 0;
-        let mut bottom_row = input.height() - 1;
-        let mut right_column = input.width() - 1;
-        let mut rv = Vec::new();
+    impl<T> Clone for Traversal<'_, T> {
+        fn clone(&self) -> Self {
+            Self { ..*self }
+        }
+    }
 
-        for _ in 0..(input.width() * input.height()) {
-            rv.push(input[(col, row)]);
-            match direction {
-                Direction::East => {
-                    if col < right_column {
-                        col += 1;
-                    } else {
-                        direction = Direction::South;
-                        row += 1;
-                        top_row += 1;
+    impl<'a, T> Traversal<'a, T> {
+        fn item(&self, idx: usize) -> ((usize, usize), &'a T) {
+            let (width, height) = (self.grid.width(), self.grid.height());
+            let (col, row) = match self.order {
+                Order::Spiral(rotation, direction) => {
+                    // counter-clockwise is clockwise on the transposed grid, and outward is
+                    // inward backwards with the rotation flipped
+                    let (idx, rotation) = match direction {
+                        SpiralDirection::Inward => (idx, rotation),
+                        SpiralDirection::Outward => (
+                            self.grid.size() - 1 - idx,
+                            match rotation {
+                                Rotation::Clockwise => Rotation::CounterClockwise,
+                                Rotation::CounterClockwise => Rotation::Clockwise,
+                            },
+                        ),
                     };
+                    match rotation {
+                        Rotation::Clockwise => spiral_coordinate(width, height, idx),
+                        Rotation::CounterClockwise => {
+                            let (row, col) = spiral_coordinate(height, width, idx);
+                            (col, row)
+                        }
+                    }
                 }
-                Direction::South => {
-                    if row < bottom_row {
-                        row += 1
-                    } else {
-                        direction = Direction::West;
-                        col -= 1;
-                        right_column -= 1;
-                    };
+                Order::Wave(Axis::Rows) => {
+                    let (row, col) = (idx / width, idx % width);
+                    (if row % 2 == 0 { col } else { width - 1 - col }, row)
                 }
-                Direction::West => {
-                    if col > left_column {
-                        col -= 1
-                    } else {
-                        direction = Direction::North;
-                        row -= 1;
-                        bottom_row -= 1;
-                    };
+                Order::Wave(Axis::Columns) => {
+                    let (col, row) = (idx / height, idx % height);
+                    (col, if col % 2 == 0 { row } else { height - 1 - row })
                 }
-                Direction::North => {
-                    if row > top_row {
-                        row -= 1;
-                    } else {
-                        direction = Direction::East;
-                        col += 1;
-                        left_column += 1;
-                    }
+                Order::Diagonal => diagonal_coordinate(width, height, idx),
+                Order::AntiDiagonal => {
+                    let (col, row) = diagonal_coordinate(width, height, idx);
+                    (width - 1 - col, row)
                 }
-            }
+            };
+            ((col, row), &self.grid[(col, row)])
         }
-        rv
     }
 
-    pub fn reverse_wave_print(input: Vec2d<u32>) -> Vec<u32> {
-        let mut rv = Vec::new();
-        for col in (0..input.width()).rev().step_by(2) {
-            for row in 0..input.height() {
-                rv.push(input[(col, row)]);
+    impl<'a, T> Iterator for Traversal<'a, T> {
+        type Item = ((usize, usize), &'a T);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.front == self.back {
+                return None;
             }
-            if col > 0 {
-                for row in (0..input.height()).rev() {
-                    rv.push(input[(col - 1, row)]);
-                }
+            self.front += 1;
+            Some(self.item(self.front - 1))
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.back - self.front, Some(self.back - self.front))
+        }
+        fn nth(&mut self, n: usize) -> Option<Self::Item> {
+            self.front = self.front.saturating_add(n).min(self.back);
+            self.next()
+        }
+    }
+
+    impl<T> DoubleEndedIterator for Traversal<'_, T> {
+        fn next_back(&mut self) -> Option<Self::Item> {
+            if self.front == self.back {
+                return None;
             }
+            self.back -= 1;
+            Some(self.item(self.back))
+        }
+    }
+
+    impl<T> ExactSizeIterator for Traversal<'_, T> {}
+    impl<T> FusedIterator for Traversal<'_, T> {}
+
+    // The idx-th cell of a clockwise inward spiral from the top-left corner
+    fn spiral_coordinate(width: usize, height: usize, idx: usize) -> (usize, usize) {
+        // cells in the rings outside ring k
+        let outside = |k: usize| width * height - (width - 2 * k) * (height - 2 * k);
+        let rings = width.min(height).div_ceil(2);
+        let (mut lo, mut hi) = (0, rings - 1);
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if outside(mid) <= idx {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let ring = lo;
+        let (w, h) = (width - 2 * ring, height - 2 * ring);
+        let (left, top, right, bottom) = (ring, ring, ring + w - 1, ring + h - 1);
+        let step = idx - outside(ring);
+        if h == 1 {
+            (left + step, top)
+        } else if w == 1 {
+            (left, top + step)
+        } else if step < w {
+            (left + step, top)
+        } else if step < w + h - 1 {
+            (right, top + step - (w - 1))
+        } else if step < 2 * w + h - 2 {
+            (right - (step - (w + h - 2)), bottom)
+        } else {
+            (left, bottom - (step - (2 * w + h - 3)))
         }
-        rv
     }
 
-    enum Direction {
-        North,
-        South,
-        East,
-        West,
+    // The idx-th cell of a zigzag along the lines col + row = d, upwards on even d
+    fn diagonal_coordinate(width: usize, height: usize, idx: usize) -> (usize, usize) {
+        // cells on the lines before line d, by inclusion-exclusion over the triangle below it
+        let triangle = |n: usize| n * (n + 1) / 2;
+        let before = |d: usize| {
+            triangle(d) + triangle(d.saturating_sub(width + height))
+                - triangle(d.saturating_sub(width))
+                - triangle(d.saturating_sub(height))
+        };
+        let (mut lo, mut hi) = (0, width + height - 2);
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if before(mid) <= idx {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let line = lo;
+        let step = idx - before(line);
+        let (min_row, max_row) = ((line + 1).saturating_sub(width), line.min(height - 1));
+        let row = if line % 2 == 0 {
+            max_row - step
+        } else {
+            min_row + step
+        };
+        (line - row, row)
     }
 }
 
@@ -644,6 +1637,13 @@ pub mod search_2d {
     use super::print_2d::Vec2d;
 
     pub fn staircase_search(input: Vec2d<u32>, find: u32) -> Option<(usize, usize)> {
+        staircase_find(&input, &find)
+    }
+
+    pub fn staircase_find<T: Ord>(input: &Vec2d<T>, find: &T) -> Option<(usize, usize)> {
+        if input.size() == 0 {
+            return None;
+        }
         let (mut col, mut row) = (input.width() - 1, 0);
         loop {
             match find.cmp(&input[(col, row)]) {
@@ -664,27 +1664,65 @@ pub mod search_2d {
         }
         None
     }
-}
-
-pub mod mango {
-    use super::print_2d::Vec2d;
 
-    pub fn best_split(input: Vec2d<u32>) -> u64 {
-        let mut aux = Vec2d::new(input.width(), input.height(), 0_u64);
-        for col in 0..input.width() {
-            for row in 0..input.height() {
-                aux[(col, row)] =                                                   //-
-                    input[(col, row)] as u64                                        //-
-                        + (if row==0 {0} else {aux[(col, row - 1)]})                //-
-                        + (if col==0 {0} else {aux[(col - 1, row)]})                //-
-                        - (if col==0 || row==0 {0} else {aux[(col - 1, row - 1)]});
-            }
+    pub fn staircase_find_all<T: Ord>(input: &Vec2d<T>, find: &T) -> Vec<(usize, usize)> {
+        let mut rv = Vec::new();
+        if input.size() == 0 {
+            return rv;
         }
-        let mut max_reward = 0;
-        for col in 0..input.width() {
-            for row in 0..input.height() {
-                let q1 = aux[(col, row)];
-                let q2 = aux[(aux.width() - 1, row)] - q1;
+        let (mut col, mut row) = (input.width() - 1, 0);
+        loop {
+            match find.cmp(&input[(col, row)]) {
+                Ordering::Equal => {
+                    // equal values in this row can only continue to the left; the rest of the
+                    // column below is still a candidate, so move down rather than left afterwards
+                    let mut left = col;
+                    while left > 0 && input[(left - 1, row)] == *find {
+                        left -= 1;
+                    }
+                    rv.extend((left..=col).map(|c| (c, row)));
+                    if row == input.height() - 1 {
+                        break;
+                    }
+                    row += 1;
+                }
+                Ordering::Less => {
+                    if col == 0 {
+                        break;
+                    }
+                    col -= 1;
+                }
+                Ordering::Greater => {
+                    if row == input.height() - 1 {
+                        break;
+                    }
+                    row += 1;
+                }
+            }
+        }
+        rv
+    }
+}
+
+pub mod mango {
+    use super::print_2d::Vec2d;
+
+    pub fn best_split(input: Vec2d<u32>) -> u64 {
+        let mut aux = Vec2d::new(input.width(), input.height(), 0_u64);
+        for col in 0..input.width() {
+            for row in 0..input.height() {
+                aux[(col, row)] =                                                   //-
+                    input[(col, row)] as u64                                        //-
+                        + (if row==0 {0} else {aux[(col, row - 1)]})                //-
+                        + (if col==0 {0} else {aux[(col - 1, row)]})                //-
+                        - (if col==0 || row==0 {0} else {aux[(col - 1, row - 1)]});
+            }
+        }
+        let mut max_reward = 0;
+        for col in 0..input.width() {
+            for row in 0..input.height() {
+                let q1 = aux[(col, row)];
+                let q2 = aux[(aux.width() - 1, row)] - q1;
                 let q3 = aux[(col, aux.height() - 1)] - q1;
                 let q4 = aux[(aux.width() - 1, aux.height() - 1)] - q1 - q2 - q3;
                 max_reward = max_reward.max(q1.min(q2).min(q3).min(q4));
@@ -714,112 +1752,17 @@ pub mod pascal {
 }
 
 pub mod matrix {
-    use std::{
-        cell::RefCell,
-        ops::{Index, IndexMut},
-    };
-
-    pub struct Matrix2d<T>
-    where
-        T: Copy,
-    {
-        storage: Vec<T>,
-        width: usize,
-    }
-
-    #[allow(dead_code)]
-    impl<T: Copy> Matrix2d<T> {
-        pub fn new(width: usize, height: usize, initialize_with: T) -> Self {
-            Self {
-                storage: vec![initialize_with; width * height],
-                width,
-            }
-        }
-        pub fn width(&self) -> usize {
-            self.width
-        }
-        pub fn height(&self) -> usize {
-            self.storage.len() / self.width
-        }
-        pub fn size(&self) -> usize {
-            self.storage.len()
-        }
-        pub fn reduce_prefix_submatrices<U, InitU, CombinerUt, CombinerUu, RemoverUu>(
-            &self,
-            initializer_u: InitU,
-            combiner_ut: CombinerUt,
-            combiner_uu: CombinerUu,
-            remover_uu: RemoverUu,
-        ) -> Matrix2d<U>
-        where
-            U: Copy + Default,
-            InitU: Fn() -> U,
-            CombinerUt: Fn(&mut U, T),
-            CombinerUu: Fn(&mut U, U),
-            RemoverUu: Fn(&mut U, U),
-        {
-            let mut aux = Matrix2d::new(self.width(), self.height(), U::default());
-            for col in 0..self.width() {
-                for row in 0..self.height() {
-                    let mut val = initializer_u();
-                    combiner_ut(&mut val, self[(col, row)]);
-                    if row > 0 {
-                        combiner_uu(&mut val, aux[(col, row - 1)]);
-                    }
-                    if col > 0 {
-                        combiner_uu(&mut val, aux[(col - 1, row)]);
-                    }
-                    if col > 0 && row > 0 {
-                        remover_uu(&mut val, aux[(col - 1, row - 1)]);
-                    }
-                    aux[(col, row)] = val;
-                }
-            }
-            aux
-        }
-    }
-
-    impl<T: Copy> From<Vec<Vec<T>>> for Matrix2d<T> {
-        fn from(values: Vec<Vec<T>>) -> Self {
-            let width = values[0].len();
-            let storage = values
-                .into_iter()
-                .flat_map(|v| v.into_iter())
-                .collect::<Vec<T>>();
-            assert!(storage.len() % width == 0);
-            Self { storage, width }
-        }
-    }
-
-    impl<T: Copy> From<(usize, Vec<T>)> for Matrix2d<T> {
-        fn from((width, storage): (usize, Vec<T>)) -> Self {
-            assert!(storage.len() % width == 0);
-            Self { storage, width }
-        }
-    }
-
-    impl<T: Copy> Index<(usize, usize)> for Matrix2d<T> {
-        type Output = T;
+    use std::cell::RefCell;
 
-        fn index(&self, (col, row): (usize, usize)) -> &Self::Output {
-            assert!(col < self.width, "Column Index out-of-range");
-            assert!(
-                row < self.storage.len() / self.width,
-                "Row Index out-of-range"
-            );
-            self.storage.index(row * self.width + col)
-        }
-    }
+    pub use crate::grid::Grid as Matrix2d;
 
-    impl<T: Copy> IndexMut<(usize, usize)> for Matrix2d<T> {
-        fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut Self::Output {
-            assert!(col < self.width, "Column Index out-of-range");
-            assert!(
-                row < self.storage.len() / self.width,
-                "Row Index out-of-range"
-            );
-            self.storage.index_mut(row * self.width + col)
-        }
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub enum QueryBackend {
+        // inclusion-exclusion table: O(1) queries, rebuilt in full after any update
+        #[default]
+        PrefixTable,
+        // 2-D Fenwick tree: O(log(width) * log(height)) queries and updates
+        Fenwick,
     }
 
     pub struct QueryableMatrix2d<T, U, InitU, CombinerUt, CombinerUu, RemoverUu>
@@ -832,6 +1775,7 @@ pub mod matrix {
         RemoverUu: Fn(&mut U, U),
     {
         matrix: Matrix2d<T>,
+        backend: QueryBackend,
         reduced_submatrices: RefCell<Option<Matrix2d<U>>>,
         initializer_u: InitU,
         combiner_ut: CombinerUt,
@@ -850,6 +1794,39 @@ pub mod matrix {
         CombinerUu: Fn(&mut U, U),
         RemoverUu: Fn(&mut U, U),
     {
+        pub fn with_backend(mut self, backend: QueryBackend) -> Self {
+            self.backend = backend;
+            self.reduced_submatrices = RefCell::new(None);
+            self
+        }
+        pub fn backend(&self) -> QueryBackend {
+            self.backend
+        }
+        pub fn get(&self, col: usize, row: usize) -> T {
+            self.matrix[(col, row)]
+        }
+        pub fn set(&mut self, col: usize, row: usize, value: T) {
+            let previous = std::mem::replace(&mut self.matrix[(col, row)], value);
+            let (previous, value) = (self.reduce(previous), self.reduce(value));
+            match (self.backend, self.reduced_submatrices.get_mut()) {
+                (QueryBackend::Fenwick, Some(tree)) => {
+                    let mut node_col = col + 1;
+                    while node_col < tree.width() {
+                        let mut node_row = row + 1;
+                        while node_row < tree.height() {
+                            let node = &mut tree[(node_col, node_row)];
+                            (self.remover_uu)(node, previous);
+                            (self.combiner_uu)(node, value);
+                            node_row += node_row & node_row.wrapping_neg();
+                        }
+                        node_col += node_col & node_col.wrapping_neg();
+                    }
+                }
+                // the prefix table is rebuilt on the next query
+                (QueryBackend::PrefixTable, aux) => *aux = None,
+                (QueryBackend::Fenwick, None) => (),
+            }
+        }
         pub fn reduced_submatrix_value(
             &self,
             col1: usize,
@@ -859,29 +1836,88 @@ pub mod matrix {
         ) -> U {
             self.reduced_submatrices
                 .borrow_mut()
-                .get_or_insert_with(|| {
-                    self.matrix.reduce_prefix_submatrices(
+                .get_or_insert_with(|| match self.backend {
+                    QueryBackend::PrefixTable => self.matrix.reduce_prefix_submatrices(
                         &self.initializer_u,
                         &self.combiner_ut,
                         &self.combiner_uu,
                         &self.remover_uu,
-                    )
+                    ),
+                    QueryBackend::Fenwick => self.build_fenwick(),
                 });
             let rv = self.reduced_submatrices.borrow().as_ref().map(|aux| {
-                let mut rv = aux[(col2, row2)];
+                let prefix = |col, row| self.prefix_value(aux, col, row);
+                let mut rv = prefix(col2, row2);
                 if col1 > 0 && row1 > 0 {
-                    (self.combiner_uu)(&mut rv, aux[(col1 - 1, row1 - 1)])
+                    (self.combiner_uu)(&mut rv, prefix(col1 - 1, row1 - 1))
                 }
                 if row1 > 0 {
-                    (self.remover_uu)(&mut rv, aux[(col2, row1 - 1)])
+                    (self.remover_uu)(&mut rv, prefix(col2, row1 - 1))
                 };
                 if col1 > 0 {
-                    (self.remover_uu)(&mut rv, aux[(col1 - 1, row2)])
+                    (self.remover_uu)(&mut rv, prefix(col1 - 1, row2))
                 };
                 rv
             });
             rv.unwrap()
         }
+
+        fn reduce(&self, value: T) -> U {
+            let mut rv = (self.initializer_u)();
+            (self.combiner_ut)(&mut rv, value);
+            rv
+        }
+        // the reduction of the submatrix from (0, 0) to (col, row) inclusive
+        fn prefix_value(&self, aux: &Matrix2d<U>, col: usize, row: usize) -> U {
+            match self.backend {
+                QueryBackend::PrefixTable => aux[(col, row)],
+                QueryBackend::Fenwick => {
+                    let mut rv = (self.initializer_u)();
+                    let mut node_col = col + 1;
+                    while node_col > 0 {
+                        let mut node_row = row + 1;
+                        while node_row > 0 {
+                            (self.combiner_uu)(&mut rv, aux[(node_col, node_row)]);
+                            node_row &= node_row - 1;
+                        }
+                        node_col &= node_col - 1;
+                    }
+                    rv
+                }
+            }
+        }
+        // 1-based on both axes, with node (c, r) covering the lowbit(c) columns ending at column
+        // c - 1 and the lowbit(r) rows ending at row r - 1
+        fn build_fenwick(&self) -> Matrix2d<U> {
+            let (width, height) = (self.matrix.width() + 1, self.matrix.height() + 1);
+            let mut tree = Matrix2d::new(width, height, (self.initializer_u)());
+            for col in 1..width {
+                for row in 1..height {
+                    tree[(col, row)] = self.reduce(self.matrix[(col - 1, row - 1)]);
+                }
+            }
+            // push partial reductions to their parents along each axis in turn, for an
+            // O(width * height) build
+            for row in 1..height {
+                for col in 1..width {
+                    let parent = col + (col & col.wrapping_neg());
+                    if parent < width {
+                        let child = tree[(col, row)];
+                        (self.combiner_uu)(&mut tree[(parent, row)], child);
+                    }
+                }
+            }
+            for col in 1..width {
+                for row in 1..height {
+                    let parent = row + (row & row.wrapping_neg());
+                    if parent < height {
+                        let child = tree[(col, row)];
+                        (self.combiner_uu)(&mut tree[(col, parent)], child);
+                    }
+                }
+            }
+            tree
+        }
     }
 
     impl<T, U, InitU, CombinerUt, CombinerUu, RemoverUu>
@@ -906,7 +1942,8 @@ pub mod matrix {
             ),
         ) -> Self {
             Self {
-                matrix: Matrix2d::from((width, storage)),
+                matrix: Matrix2d::try_from((width, storage)).unwrap(),
+                backend: QueryBackend::default(),
                 reduced_submatrices: RefCell::new(None),
                 initializer_u,
                 combiner_ut,
@@ -1243,7 +2280,9 @@ pub mod fruits {
 }
 
 pub mod image {
-    use super::print_2d::Vec2d;
+    use std::ops::Range;
+
+    use super::print_2d::{Rotation, Vec2d};
 
     pub fn rotate_90_degrees(image: &mut Vec2d<u32>) {
         assert!(image.width() == image.height(), "image must be square");
@@ -1274,121 +2313,770 @@ pub mod image {
             right_idx.1 += 1;
         }
     }
-}
-
-pub mod zeroes {
-    use std::collections::HashSet;
 
-    use super::print_2d::Vec2d;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Angle {
+        Deg90,
+        Deg180,
+        Deg270,
+    }
 
-    pub fn make_zeroes(mut matrix: Vec2d<u32>) -> Vec2d<u32> {
-        let mut columns_to_zero = HashSet::new();
-        let mut rows_to_zero = HashSet::new();
-        for col in 0..matrix.width() {
-            for row in 0..matrix.height() {
-                if matrix[(col, row)] == 0 {
-                    columns_to_zero.insert(col);
-                    rows_to_zero.insert(row);
-                }
-            }
-        }
-        for col in columns_to_zero {
-            for row in 0..matrix.height() {
-                matrix[(col, row)] = 0;
+    pub fn rotate<T>(image: &mut Vec2d<T>, angle: Angle, rotation: Rotation) {
+        match (angle, rotation) {
+            (Angle::Deg180, _) => rotate_180(image),
+            (Angle::Deg90, Rotation::Clockwise) | (Angle::Deg270, Rotation::CounterClockwise) => {
+                image.transpose_in_place();
+                flip_horizontal(image);
             }
-        }
-        for row in rows_to_zero {
-            for col in 0..matrix.width() {
-                matrix[(col, row)] = 0;
+            (Angle::Deg90, Rotation::CounterClockwise) | (Angle::Deg270, Rotation::Clockwise) => {
+                image.transpose_in_place();
+                flip_vertical(image);
             }
         }
-        matrix
     }
-}
 
-pub mod xoring {
+    pub fn rotate_180<T>(image: &mut Vec2d<T>) {
+        image.as_mut_slice().reverse();
+    }
 
-    // LIMITATIONS: This function will produce a meaningless result if there is more than 1 unique number in the list (including zero)
-    pub fn find_unique_number(input: impl IntoIterator<Item = u32>) -> Option<u32> {
-        let mut count_of_zeroes = 0;
-        let rv = input
-            .into_iter()
-            .inspect(|v| {
-                if *v == 0 {
-                    count_of_zeroes += 1
-                }
-            })
-            .reduce(|accum, next| accum ^ next);
-        if let Some(0) = rv {
-            if count_of_zeroes & 1 == 1 {
-                return Some(0);
-            } else {
-                return None;
-            }
+    // mirrors left to right
+    pub fn flip_horizontal<T>(image: &mut Vec2d<T>) {
+        for row in image.rows_mut() {
+            row.reverse();
         }
-        rv
     }
-}
 
-pub mod modulus {
+    // mirrors top to bottom
+    pub fn flip_vertical<T>(image: &mut Vec2d<T>) {
+        let (width, height) = (image.width(), image.height());
+        let pixels = image.as_mut_slice();
+        for row in 0..height / 2 {
+            let (top, bottom) = pixels.split_at_mut((height - 1 - row) * width);
+            top[row * width..(row + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
 
-    pub fn powmod(base: u32, exp: u32, m: u32) -> u32 {
-        let mut base = base as u64;
-        let mut exp = exp;
-        let m = m as u64;
+    // mirrors across the main diagonal, from the top-left corner
+    pub fn transpose<T>(image: &mut Vec2d<T>) {
+        image.transpose_in_place();
+    }
 
-        let mut result = 1 % m;
-        while exp > 0 {
-            if exp & 1 == 1 {
-                result = (result * base) % m
+    // mirrors across the anti-diagonal, from the top-right corner
+    pub fn anti_transpose<T>(image: &mut Vec2d<T>) {
+        image.transpose_in_place();
+        rotate_180(image);
+    }
+
+    pub fn crop<T: Clone>(image: &Vec2d<T>, cols: Range<usize>, rows: Range<usize>) -> Vec2d<T> {
+        image.view(cols, rows).to_grid()
+    }
+
+    pub fn pad<T: Clone>(
+        image: &Vec2d<T>,
+        (left, top, right, bottom): (usize, usize, usize, usize),
+        fill: T,
+    ) -> Vec2d<T> {
+        let mut padded = Vec2d::new(
+            left + image.width() + right,
+            top + image.height() + bottom,
+            fill,
+        );
+        if image.size() > 0 {
+            let mut window = padded.view_mut(left..left + image.width(), top..top + image.height());
+            for (target, source) in window.rows_mut().zip(image.rows()) {
+                target.clone_from_slice(source);
             }
-            base = (base * base) % m;
-            exp >>= 1;
         }
-        result as u32
+        padded
     }
-}
 
-pub mod earth {
+    // The symmetries of a rectangle's pixel grid
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Dihedral {
+        Identity,
+        Rotate90,
+        Rotate180,
+        Rotate270,
+        FlipHorizontal,
+        FlipVertical,
+        Transpose,
+        AntiTranspose,
+    }
 
-    pub fn level_up_by_powers_of_2(mut level_to_reach: u32) -> u32 {
-        let mut rv = 0;
-        while level_to_reach > 0 {
-            let mut first_jump = level_to_reach;
-            while first_jump & (first_jump - 1) > 0 {
-                first_jump -= 1;
+    impl Dihedral {
+        pub const ALL: [Dihedral; 8] = [
+            Dihedral::Identity,
+            Dihedral::Rotate90,
+            Dihedral::Rotate180,
+            Dihedral::Rotate270,
+            Dihedral::FlipHorizontal,
+            Dihedral::FlipVertical,
+            Dihedral::Transpose,
+            Dihedral::AntiTranspose,
+        ];
+
+        // rotations are clockwise
+        pub fn apply<T>(self, image: &mut Vec2d<T>) {
+            match self {
+                Dihedral::Identity => (),
+                Dihedral::Rotate90 => rotate(image, Angle::Deg90, Rotation::Clockwise),
+                Dihedral::Rotate180 => rotate_180(image),
+                Dihedral::Rotate270 => rotate(image, Angle::Deg270, Rotation::Clockwise),
+                Dihedral::FlipHorizontal => flip_horizontal(image),
+                Dihedral::FlipVertical => flip_vertical(image),
+                Dihedral::Transpose => transpose(image),
+                Dihedral::AntiTranspose => anti_transpose(image),
             }
-            rv += 1;
-            level_to_reach -= first_jump;
         }
-        rv
+        // the single transform equal to applying self and then next
+        pub fn then(self, next: Dihedral) -> Dihedral {
+            let (turns, flipped) = self.parts();
+            let (next_turns, next_flipped) = next.parts();
+            // a flip after turning undoes the turn's direction: F R^k = R^-k F
+            let turns = if next_flipped {
+                (next_turns + 4 - turns) % 4
+            } else {
+                (next_turns + turns) % 4
+            };
+            Dihedral::from_parts(turns, flipped != next_flipped)
+        }
+        pub fn inverse(self) -> Dihedral {
+            match self {
+                Dihedral::Rotate90 => Dihedral::Rotate270,
+                Dihedral::Rotate270 => Dihedral::Rotate90,
+                other => other,
+            }
+        }
+        // as clockwise quarter turns after an optional horizontal flip
+        fn parts(self) -> (u8, bool) {
+            match self {
+                Dihedral::Identity => (0, false),
+                Dihedral::Rotate90 => (1, false),
+                Dihedral::Rotate180 => (2, false),
+                Dihedral::Rotate270 => (3, false),
+                Dihedral::FlipHorizontal => (0, true),
+                Dihedral::AntiTranspose => (1, true),
+                Dihedral::FlipVertical => (2, true),
+                Dihedral::Transpose => (3, true),
+            }
+        }
+        fn from_parts(turns: u8, flipped: bool) -> Dihedral {
+            *Dihedral::ALL
+                .iter()
+                .find(|transform| transform.parts() == (turns, flipped))
+                .unwrap()
+        }
     }
-}
 
-pub mod subset {
+    // Netpbm images: PBM bitmaps, PGM graymaps and PPM pixmaps, in plain (ASCII) or binary form
+    pub mod netpbm {
+        use std::io::{self, BufRead, Read, Write};
 
-    use std::cmp::Ordering;
+        pub use self::error::{NetpbmError, NetpbmErrorKind};
+        use super::super::print_2d::Vec2d;
 
-    //use awint::prelude::*;
-    use bitvec::prelude::*;
+        // plain files must keep their lines to at most this many characters
+        const MAX_PLAIN_LINE: usize = 70;
 
-    pub fn subset_sum_queries(data: Vec<i32>, queries: impl IntoIterator<Item = u32>) -> Vec<bool> {
-        let subset_sum_flags = compute_subset_sum_flags(data);
-        compute_list_of_queries_results(subset_sum_flags, queries)
-    }
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Format {
+            Bitmap,
+            Graymap,
+            Pixmap,
+        }
 
-    fn compute_list_of_queries_results(
-        subset_sum_flags: BitVec,
-        queries: impl IntoIterator<Item = u32>,
-    ) -> Vec<bool> {
-        queries
-            .into_iter()
-            .map(|sum| subset_sum_flags[sum as usize])
-            .collect()
-    }
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Encoding {
+            Plain,
+            Binary,
+        }
 
-    fn compute_subset_sum_flags(data: Vec<i32>) -> BitVec {
-        let mut subset_sum_flags = bitvec![0_u64;100_000];
+        pub trait Sample: Copy {
+            const MAX: u16;
+            fn from_u16(value: u16) -> Self;
+            fn to_u16(self) -> u16;
+        }
+
+        impl Sample for u8 {
+            const MAX: u16 = u8::MAX as u16;
+            fn from_u16(value: u16) -> Self {
+                value as u8
+            }
+            fn to_u16(self) -> u16 {
+                self as u16
+            }
+        }
+
+        impl Sample for u16 {
+            const MAX: u16 = u16::MAX;
+            fn from_u16(value: u16) -> Self {
+                value
+            }
+            fn to_u16(self) -> u16 {
+                self
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Graymap<T> {
+            pub image: Vec2d<T>,
+            pub maxval: T,
+        }
+
+        // Pixels are packed as 0x00RRGGBB, leaving a byte per channel, so 16-bit pixmaps (a
+        // maxval above 255) are not supported
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Pixmap {
+            pub image: Vec2d<u32>,
+            pub maxval: u8,
+        }
+
+        struct Header {
+            format: Format,
+            encoding: Encoding,
+            width: usize,
+            height: usize,
+            maxval: u16,
+        }
+
+        // 1 is black, 0 is white
+        pub fn read_pbm<R: BufRead>(mut reader: R) -> Result<Vec2d<u8>, NetpbmError> {
+            let header = read_header(&mut reader, Format::Bitmap)?;
+            let size = image_size(&header, 1)?;
+            let mut pixels = Vec::new();
+            match header.encoding {
+                Encoding::Plain => {
+                    while pixels.len() < size {
+                        skip_separators(&mut reader)?;
+                        match next_byte(&mut reader)? {
+                            b'0' => pixels.push(0),
+                            b'1' => pixels.push(1),
+                            _ => return Err(NetpbmError::new(NetpbmErrorKind::InvalidData)),
+                        }
+                    }
+                }
+                Encoding::Binary => {
+                    // rows grow with the bytes actually read, never just on the header's word
+                    let row_len = header.width.div_ceil(8);
+                    let mut row = Vec::new();
+                    for _ in 0..header.height {
+                        row.clear();
+                        (&mut reader).take(row_len as u64).read_to_end(&mut row)?;
+                        if row.len() < row_len {
+                            return Err(NetpbmError::new(NetpbmErrorKind::UnexpectedEof));
+                        }
+                        pixels
+                            .extend((0..header.width).map(|col| row[col / 8] >> (7 - col % 8) & 1));
+                    }
+                }
+            }
+            Ok(into_image(header.width, pixels))
+        }
+
+        // any non-zero pixel is written as black
+        pub fn write_pbm<W: Write>(
+            mut writer: W,
+            image: &Vec2d<u8>,
+            encoding: Encoding,
+        ) -> Result<(), NetpbmError> {
+            let magic = if encoding == Encoding::Plain { 1 } else { 4 };
+            write!(writer, "P{magic}\n{} {}\n", image.width(), image.height())?;
+            match encoding {
+                Encoding::Plain => write_plain(
+                    &mut writer,
+                    image.width(),
+                    image.iter().map(|&pixel| (pixel != 0) as u16),
+                )?,
+                Encoding::Binary => {
+                    for row in image.rows() {
+                        let mut packed = vec![0u8; row.len().div_ceil(8)];
+                        for (col, _) in row.iter().enumerate().filter(|(_, &pixel)| pixel != 0) {
+                            packed[col / 8] |= 0x80 >> (col % 8);
+                        }
+                        writer.write_all(&packed)?;
+                    }
+                }
+            }
+            Ok(writer.flush()?)
+        }
+
+        pub fn read_pgm<T, R>(mut reader: R) -> Result<Graymap<T>, NetpbmError>
+        where
+            T: Sample,
+            R: BufRead,
+        {
+            let header = read_header(&mut reader, Format::Graymap)?;
+            if header.maxval > T::MAX {
+                return Err(NetpbmError::new(NetpbmErrorKind::UnsupportedMaxval(
+                    header.maxval as u32,
+                )));
+            }
+            let samples = read_samples(&mut reader, &header, image_size(&header, 1)?)?;
+            Ok(Graymap {
+                image: into_image(header.width, samples.into_iter().map(T::from_u16).collect()),
+                maxval: T::from_u16(header.maxval),
+            })
+        }
+
+        pub fn write_pgm<T, W>(
+            mut writer: W,
+            graymap: &Graymap<T>,
+            encoding: Encoding,
+        ) -> Result<(), NetpbmError>
+        where
+            T: Sample,
+            W: Write,
+        {
+            let maxval = graymap.maxval.to_u16();
+            let image = &graymap.image;
+            let magic = if encoding == Encoding::Plain { 2 } else { 5 };
+            write_header(&mut writer, magic, image.width(), image.height(), maxval)?;
+            let samples = image.iter().map(|pixel| pixel.to_u16());
+            write_samples(&mut writer, image.width(), maxval, samples, encoding)?;
+            Ok(writer.flush()?)
+        }
+
+        pub fn read_ppm<R: BufRead>(mut reader: R) -> Result<Pixmap, NetpbmError> {
+            let header = read_header(&mut reader, Format::Pixmap)?;
+            if header.maxval > u8::MAX as u16 {
+                return Err(NetpbmError::new(NetpbmErrorKind::UnsupportedMaxval(
+                    header.maxval as u32,
+                )));
+            }
+            let samples = read_samples(&mut reader, &header, image_size(&header, 3)?)?;
+            let pixels = samples
+                .chunks_exact(3)
+                .map(|rgb| (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32)
+                .collect();
+            Ok(Pixmap {
+                image: into_image(header.width, pixels),
+                maxval: header.maxval as u8,
+            })
+        }
+
+        // the unused top byte of each pixel is ignored
+        pub fn write_ppm<W: Write>(
+            mut writer: W,
+            pixmap: &Pixmap,
+            encoding: Encoding,
+        ) -> Result<(), NetpbmError> {
+            let maxval = pixmap.maxval as u16;
+            let image = &pixmap.image;
+            let magic = if encoding == Encoding::Plain { 3 } else { 6 };
+            write_header(&mut writer, magic, image.width(), image.height(), maxval)?;
+            let samples = image.iter().flat_map(|&pixel| {
+                [pixel >> 16, pixel >> 8, pixel].map(|channel| channel as u8 as u16)
+            });
+            write_samples(&mut writer, image.width() * 3, maxval, samples, encoding)?;
+            Ok(writer.flush()?)
+        }
+
+        fn read_header<R: BufRead>(
+            reader: &mut R,
+            expected: Format,
+        ) -> Result<Header, NetpbmError> {
+            let mut magic = [0; 2];
+            reader.read_exact(&mut magic)?;
+            let (format, encoding) = match magic {
+                [b'P', b'1'] => (Format::Bitmap, Encoding::Plain),
+                [b'P', b'2'] => (Format::Graymap, Encoding::Plain),
+                [b'P', b'3'] => (Format::Pixmap, Encoding::Plain),
+                [b'P', b'4'] => (Format::Bitmap, Encoding::Binary),
+                [b'P', b'5'] => (Format::Graymap, Encoding::Binary),
+                [b'P', b'6'] => (Format::Pixmap, Encoding::Binary),
+                _ => return Err(NetpbmError::new(NetpbmErrorKind::BadMagicNumber)),
+            };
+            if format != expected {
+                return Err(NetpbmError::new(NetpbmErrorKind::WrongFormat {
+                    expected,
+                    found: format,
+                }));
+            }
+            let width = read_header_number(reader)? as usize;
+            let height = read_header_number(reader)? as usize;
+            let maxval = if format == Format::Bitmap {
+                1
+            } else {
+                match read_header_number(reader)? {
+                    maxval @ 1..=65535 => maxval as u16,
+                    maxval => {
+                        return Err(NetpbmError::new(NetpbmErrorKind::UnsupportedMaxval(maxval)))
+                    }
+                }
+            };
+            // a single whitespace character separates the header from a binary raster
+            if encoding == Encoding::Binary && !next_byte(reader)?.is_ascii_whitespace() {
+                return Err(NetpbmError::new(NetpbmErrorKind::InvalidHeader));
+            }
+            Ok(Header {
+                format,
+                encoding,
+                width,
+                height,
+                maxval,
+            })
+        }
+
+        fn read_header_number<R: BufRead>(reader: &mut R) -> Result<u32, NetpbmError> {
+            read_number(reader).map_err(|err| match err.kind() {
+                NetpbmErrorKind::InvalidData => NetpbmError::new(NetpbmErrorKind::InvalidHeader),
+                _ => err,
+            })
+        }
+
+        fn image_size(header: &Header, samples_per_pixel: usize) -> Result<usize, NetpbmError> {
+            debug_assert!(header.format != Format::Bitmap || samples_per_pixel == 1);
+            header
+                .width
+                .checked_mul(header.height)
+                .and_then(|size| size.checked_mul(samples_per_pixel))
+                .ok_or(NetpbmError::new(NetpbmErrorKind::InvalidHeader))
+        }
+
+        fn into_image<T>(width: usize, pixels: Vec<T>) -> Vec2d<T> {
+            Vec2d::try_from((width, pixels)).expect("pixel count is a multiple of the width")
+        }
+
+        fn read_samples<R: BufRead>(
+            reader: &mut R,
+            header: &Header,
+            count: usize,
+        ) -> Result<Vec<u16>, NetpbmError> {
+            let mut samples = Vec::new();
+            while samples.len() < count {
+                let sample = match header.encoding {
+                    Encoding::Plain => read_number(reader)?,
+                    Encoding::Binary if header.maxval <= u8::MAX as u16 => {
+                        next_byte(reader)? as u32
+                    }
+                    Encoding::Binary => {
+                        let mut bytes = [0; 2];
+                        reader.read_exact(&mut bytes)?;
+                        u16::from_be_bytes(bytes) as u32
+                    }
+                };
+                if sample > header.maxval as u32 {
+                    return Err(NetpbmError::new(NetpbmErrorKind::SampleOutOfRange {
+                        sample,
+                        maxval: header.maxval,
+                    }));
+                }
+                samples.push(sample as u16);
+            }
+            Ok(samples)
+        }
+
+        fn write_header<W: Write>(
+            writer: &mut W,
+            magic: u8,
+            width: usize,
+            height: usize,
+            maxval: u16,
+        ) -> Result<(), NetpbmError> {
+            if maxval == 0 {
+                return Err(NetpbmError::new(NetpbmErrorKind::UnsupportedMaxval(0)));
+            }
+            Ok(write!(writer, "P{magic}\n{width} {height}\n{maxval}\n")?)
+        }
+
+        fn write_samples<W, I>(
+            writer: &mut W,
+            row_len: usize,
+            maxval: u16,
+            samples: I,
+            encoding: Encoding,
+        ) -> Result<(), NetpbmError>
+        where
+            W: Write,
+            I: Iterator<Item = u16>,
+        {
+            let samples = samples
+                .map(|sample| {
+                    if sample > maxval {
+                        Err(NetpbmError::new(NetpbmErrorKind::SampleOutOfRange {
+                            sample: sample as u32,
+                            maxval,
+                        }))
+                    } else {
+                        Ok(sample)
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            match encoding {
+                Encoding::Plain => write_plain(writer, row_len, samples.into_iter())?,
+                Encoding::Binary => {
+                    let bytes: Vec<u8> = if maxval <= u8::MAX as u16 {
+                        samples.into_iter().map(|sample| sample as u8).collect()
+                    } else {
+                        samples.into_iter().flat_map(u16::to_be_bytes).collect()
+                    };
+                    writer.write_all(&bytes)?;
+                }
+            }
+            Ok(())
+        }
+
+        // one row per line, wrapping rows that would not fit
+        fn write_plain<W, I>(writer: &mut W, row_len: usize, samples: I) -> io::Result<()>
+        where
+            W: Write,
+            I: Iterator<Item = u16>,
+        {
+            let mut line = String::new();
+            for (idx, sample) in samples.enumerate() {
+                let token = sample.to_string();
+                if !line.is_empty() && line.len() + 1 + token.len() > MAX_PLAIN_LINE {
+                    writeln!(writer, "{line}")?;
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&token);
+                if (idx + 1) % row_len == 0 {
+                    writeln!(writer, "{line}")?;
+                    line.clear();
+                }
+            }
+            if !line.is_empty() {
+                writeln!(writer, "{line}")?;
+            }
+            Ok(())
+        }
+
+        fn peek_byte<R: BufRead>(reader: &mut R) -> io::Result<Option<u8>> {
+            Ok(reader.fill_buf()?.first().copied())
+        }
+
+        fn next_byte<R: BufRead>(reader: &mut R) -> Result<u8, NetpbmError> {
+            let byte =
+                peek_byte(reader)?.ok_or(NetpbmError::new(NetpbmErrorKind::UnexpectedEof))?;
+            reader.consume(1);
+            Ok(byte)
+        }
+
+        // whitespace, and comments running from '#' to the end of the line
+        fn skip_separators<R: BufRead>(reader: &mut R) -> Result<(), NetpbmError> {
+            let mut in_comment = false;
+            while let Some(byte) = peek_byte(reader)? {
+                match byte {
+                    b'#' => in_comment = true,
+                    b'\n' | b'\r' => in_comment = false,
+                    _ if in_comment || byte.is_ascii_whitespace() => (),
+                    _ => break,
+                }
+                reader.consume(1);
+            }
+            Ok(())
+        }
+
+        fn read_number<R: BufRead>(reader: &mut R) -> Result<u32, NetpbmError> {
+            skip_separators(reader)?;
+            let mut number: Option<u32> = None;
+            while let Some(byte @ b'0'..=b'9') = peek_byte(reader)? {
+                reader.consume(1);
+                number = number
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|number| number.checked_add((byte - b'0') as u32));
+                if number.is_none() {
+                    return Err(NetpbmError::new(NetpbmErrorKind::InvalidData));
+                }
+            }
+            match (number, peek_byte(reader)?) {
+                (None, None) => Err(NetpbmError::new(NetpbmErrorKind::UnexpectedEof)),
+                (None, _) => Err(NetpbmError::new(NetpbmErrorKind::InvalidData)),
+                (Some(number), _) => Ok(number),
+            }
+        }
+
+        pub mod error {
+            use std::{fmt, io};
+
+            use super::Format;
+
+            #[derive(Debug)]
+            pub struct NetpbmError {
+                kind: NetpbmErrorKind,
+            }
+
+            impl NetpbmError {
+                pub fn new(kind: NetpbmErrorKind) -> Self {
+                    Self { kind }
+                }
+                pub fn kind(&self) -> &NetpbmErrorKind {
+                    &self.kind
+                }
+            }
+
+            #[derive(Debug)]
+            pub enum NetpbmErrorKind {
+                Io(io::Error),
+                UnexpectedEof,
+                BadMagicNumber,
+                WrongFormat { expected: Format, found: Format },
+                InvalidHeader,
+                InvalidData,
+                UnsupportedMaxval(u32),
+                SampleOutOfRange { sample: u32, maxval: u16 },
+            }
+
+            impl From<io::Error> for NetpbmError {
+                fn from(err: io::Error) -> Self {
+                    if err.kind() == io::ErrorKind::UnexpectedEof {
+                        NetpbmError::new(NetpbmErrorKind::UnexpectedEof)
+                    } else {
+                        NetpbmError::new(NetpbmErrorKind::Io(err))
+                    }
+                }
+            }
+
+            impl fmt::Display for NetpbmError {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    match &self.kind {
+                        NetpbmErrorKind::Io(err) => write!(f, "i/o error: {err}"),
+                        NetpbmErrorKind::UnexpectedEof => write!(f, "image data ends early"),
+                        NetpbmErrorKind::BadMagicNumber => write!(f, "not a netpbm image"),
+                        NetpbmErrorKind::WrongFormat { expected, found } => {
+                            write!(f, "expected a {expected:?} image, found a {found:?}")
+                        }
+                        NetpbmErrorKind::InvalidHeader => write!(f, "malformed image header"),
+                        NetpbmErrorKind::InvalidData => write!(f, "malformed image data"),
+                        NetpbmErrorKind::UnsupportedMaxval(maxval) => {
+                            write!(f, "maximum value {maxval} is not supported")
+                        }
+                        NetpbmErrorKind::SampleOutOfRange { sample, maxval } => {
+                            write!(f, "sample {sample} exceeds maximum value {maxval}")
+                        }
+                    }
+                }
+            }
+
+            impl std::error::Error for NetpbmError {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    match &self.kind {
+                        NetpbmErrorKind::Io(err) => Some(err),
+                        _ => None,
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub mod zeroes {
+    use std::collections::HashSet;
+
+    use super::print_2d::Vec2d;
+
+    pub fn make_zeroes(mut matrix: Vec2d<u32>) -> Vec2d<u32> {
+        let mut columns_to_zero = HashSet::new();
+        let mut rows_to_zero = HashSet::new();
+        for col in 0..matrix.width() {
+            for row in 0..matrix.height() {
+                if matrix[(col, row)] == 0 {
+                    columns_to_zero.insert(col);
+                    rows_to_zero.insert(row);
+                }
+            }
+        }
+        for col in columns_to_zero {
+            for row in 0..matrix.height() {
+                matrix[(col, row)] = 0;
+            }
+        }
+        for row in rows_to_zero {
+            for col in 0..matrix.width() {
+                matrix[(col, row)] = 0;
+            }
+        }
+        matrix
+    }
+}
+
+pub mod xoring {
+
+    // LIMITATIONS: This function will produce a meaningless result if there is more than 1 unique number in the list (including zero)
+    pub fn find_unique_number(input: impl IntoIterator<Item = u32>) -> Option<u32> {
+        let mut count_of_zeroes = 0;
+        let rv = input
+            .into_iter()
+            .inspect(|v| {
+                if *v == 0 {
+                    count_of_zeroes += 1
+                }
+            })
+            .reduce(|accum, next| accum ^ next);
+        if let Some(0) = rv {
+            if count_of_zeroes & 1 == 1 {
+                return Some(0);
+            } else {
+                return None;
+            }
+        }
+        rv
+    }
+}
+
+pub mod modulus {
+
+    pub fn powmod(base: u32, exp: u32, m: u32) -> u32 {
+        let mut base = base as u64;
+        let mut exp = exp;
+        let m = m as u64;
+
+        let mut result = 1 % m;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = (result * base) % m
+            }
+            base = (base * base) % m;
+            exp >>= 1;
+        }
+        result as u32
+    }
+}
+
+pub mod earth {
+
+    pub fn level_up_by_powers_of_2(mut level_to_reach: u32) -> u32 {
+        let mut rv = 0;
+        while level_to_reach > 0 {
+            let mut first_jump = level_to_reach;
+            while first_jump & (first_jump - 1) > 0 {
+                first_jump -= 1;
+            }
+            rv += 1;
+            level_to_reach -= first_jump;
+        }
+        rv
+    }
+}
+
+pub mod subset {
+
+    use std::cmp::Ordering;
+
+    //use awint::prelude::*;
+    use bitvec::prelude::*;
+
+    pub fn subset_sum_queries(data: Vec<i32>, queries: impl IntoIterator<Item = u32>) -> Vec<bool> {
+        let subset_sum_flags = compute_subset_sum_flags(data);
+        compute_list_of_queries_results(subset_sum_flags, queries)
+    }
+
+    fn compute_list_of_queries_results(
+        subset_sum_flags: BitVec,
+        queries: impl IntoIterator<Item = u32>,
+    ) -> Vec<bool> {
+        queries
+            .into_iter()
+            .map(|sum| subset_sum_flags[sum as usize])
+            .collect()
+    }
+
+    fn compute_subset_sum_flags(data: Vec<i32>) -> BitVec {
+        let mut subset_sum_flags = bitvec![0_u64;100_000];
         subset_sum_flags.set(0, true);
         for val in data {
             let original_subset_sum_flags = subset_sum_flags.clone();
@@ -1589,69 +3277,733 @@ pub mod recursion {
 pub mod divide_conquer {
 
     pub mod sort {
-        use crate::print_2d::Vec2d;
+        use std::{cmp::Ordering, mem, ptr, thread};
 
-        pub fn merge_sort<T: Ord + Copy>(data: &mut [T]) {
-            if data.len() <= 1 {
-                return;
-            }
-            let mid = data.len() / 2;
-            let (left, right) = data.split_at_mut(mid);
-            merge_sort(left);
-            merge_sort(right);
-            merge(left, right);
+        use crate::{instrument::Observer, print_2d::Vec2d};
+
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+        pub enum MergeStrategy {
+            #[default]
+            TopDown,
+            BottomUp,
+            InPlace,
         }
-        fn merge<T: Ord + Copy>(left: &mut [T], right: &mut [T]) {
-            let mut l_idx = 0;
-            let mut r_idx = 0;
-            let mut tmp = Vec::with_capacity(left.len() + right.len());
-            while l_idx < left.len() || r_idx < right.len() {
-                if r_idx >= right.len() || (l_idx < left.len() && left[l_idx] <= right[r_idx]) {
-                    tmp.push(left[l_idx]);
-                    l_idx += 1;
-                } else if l_idx >= left.len() || left[l_idx] > right[r_idx] {
-                    tmp.push(right[r_idx]);
-                    r_idx += 1;
+
+        pub fn merge_sort<T: Ord>(data: &mut [T]) {
+            merge_sort_by(data, T::cmp)
+        }
+        pub fn merge_sort_by<T, F>(data: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            merge_sort_by_with(data, MergeStrategy::default(), compare)
+        }
+        pub fn merge_sort_by_key<T, K, F>(data: &mut [T], mut key_of: F)
+        where
+            K: Ord,
+            F: FnMut(&T) -> K,
+        {
+            merge_sort_by(data, |a, b| key_of(a).cmp(&key_of(b)))
+        }
+        pub fn merge_sort_with<T: Ord>(data: &mut [T], strategy: MergeStrategy) {
+            merge_sort_by_with(data, strategy, T::cmp)
+        }
+        pub fn merge_sort_by_with<T, F>(data: &mut [T], strategy: MergeStrategy, mut compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            merge_sort_observed(data, strategy, &mut compare, &mut ())
+        }
+        pub(crate) fn merge_sort_observed<T, F, O>(
+            data: &mut [T],
+            strategy: MergeStrategy,
+            compare: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            if data.len() <= 1 || mem::size_of::<T>() == 0 {
+                return;
+            }
+            let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+            match strategy {
+                MergeStrategy::TopDown => {
+                    let mut buf = Vec::with_capacity(data.len() / 2);
+                    merge_sort_top_down(data, 0, &mut buf, &mut is_less, observer);
                 }
+                MergeStrategy::BottomUp => {
+                    let mut buf = Vec::with_capacity(data.len() / 2);
+                    merge_sort_bottom_up(data, &mut buf, &mut is_less, observer);
+                }
+                MergeStrategy::InPlace => merge_sort_in_place(data, 0, &mut is_less, observer),
             }
-            for (idx, val) in tmp.iter().take(left.len()).enumerate() {
-                left[idx] = *val;
+        }
+        fn merge_sort_top_down<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            buf: &mut Vec<T>,
+            is_less: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> bool,
+            O: Observer<T>,
+        {
+            if data.len() <= 1 {
+                return;
             }
-            for (idx, val) in tmp.iter().skip(left.len()).enumerate() {
-                right[idx] = *val;
+            observer.enter();
+            let mid = data.len() / 2;
+            merge_sort_top_down(&mut data[..mid], offset, buf, is_less, observer);
+            merge_sort_top_down(&mut data[mid..], offset + mid, buf, is_less, observer);
+            merge(data, offset, mid, buf, is_less, observer);
+            observer.leave();
+        }
+        fn merge_sort_bottom_up<T, F, O>(
+            data: &mut [T],
+            buf: &mut Vec<T>,
+            is_less: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> bool,
+            O: Observer<T>,
+        {
+            let mut width = 1;
+            while width < data.len() {
+                let mut start = 0;
+                while start + width < data.len() {
+                    let end = (start + 2 * width).min(data.len());
+                    merge(&mut data[start..end], start, width, buf, is_less, observer);
+                    start = end;
+                }
+                width *= 2;
             }
         }
-
-        pub fn quick_sort<T: Ord + Copy>(data: &mut [T]) {
+        fn merge_sort_in_place<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            is_less: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> bool,
+            O: Observer<T>,
+        {
             if data.len() <= 1 {
                 return;
             }
-            let pivot = partition(data);
-            let (left, right) = data.split_at_mut(pivot);
-            quick_sort(left);
-            quick_sort(right);
+            observer.enter();
+            let mid = data.len() / 2;
+            merge_sort_in_place(&mut data[..mid], offset, is_less, observer);
+            merge_sort_in_place(&mut data[mid..], offset + mid, is_less, observer);
+            merge_in_place(data, offset, mid, is_less, observer);
+            observer.leave();
         }
-        fn partition<T: Ord + Copy>(data: &mut [T]) -> usize {
-            if data.len() <= 1 {
-                return 0;
+
+        // Merges the sorted runs data[..mid] and data[mid..] by moving the shorter of the two out
+        // to buf, which must be empty and have capacity for it
+        pub(crate) fn merge<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            mid: usize,
+            buf: &mut Vec<T>,
+            is_less: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> bool,
+            O: Observer<T>,
+        {
+            let len = data.len();
+            if mid == 0 || mid == len {
+                return;
             }
-            let mut pivot = data.len() - 1;
-            let mut start_of_above = 0;
-            let mut current_compare = 0;
-            while current_compare < pivot {
-                if data[current_compare] <= data[pivot] {
-                    if current_compare > start_of_above {
-                        data.swap(start_of_above, current_compare);
+            observer.compare(offset + mid - 1, offset + mid);
+            if !is_less(&data[mid], &data[mid - 1]) {
+                return;
+            }
+            assert!(buf.is_empty() && buf.capacity() >= mid.min(len - mid));
+            let v = data.as_mut_ptr();
+            let b = buf.as_mut_ptr();
+            // slice index of a slot, and original slice index of an item moved out into buf
+            let at = |ptr: *mut T| offset + unsafe { ptr.offset_from(v) } as usize;
+            // Safety:
+            //   - buf has room for the shorter run and a length of zero, so it never drops what
+            //     is moved into it
+            //   - once the shorter run is moved out, every slot of data is either live or part of
+            //     the gap tracked by the hole, and the gap is always exactly as long as what is
+            //     left in buf; dropping the hole (normally or while unwinding from a panicking
+            //     comparison) moves the rest of buf into the gap, so each element is in data
+            //     exactly once afterwards
+            //   - all pointers stay within v..v+len and b..b+capacity
+            unsafe {
+                if mid <= len - mid {
+                    let from_buf = |ptr: *mut T| offset + ptr.offset_from(b) as usize;
+                    ptr::copy_nonoverlapping(v, b, mid);
+                    let mut hole = MergeHole {
+                        start: b,
+                        end: b.add(mid),
+                        dest: v,
+                    };
+                    let mut right = v.add(mid);
+                    let v_end = v.add(len);
+                    while hole.start < hole.end && right < v_end {
+                        observer.compare(from_buf(hole.start), at(right));
+                        let dest = hole.dest;
+                        if is_less(&*right, &*hole.start) {
+                            ptr::copy_nonoverlapping(right, dest, 1);
+                            right = right.add(1);
+                        } else {
+                            ptr::copy_nonoverlapping(hole.start, dest, 1);
+                            hole.start = hole.start.add(1);
+                        }
+                        hole.dest = hole.dest.add(1);
+                        observer.write(at(dest), &*dest);
+                    }
+                    let remaining = hole.end.offset_from(hole.start) as usize;
+                    for idx in 0..remaining {
+                        observer.write(at(hole.dest) + idx, &*hole.start.add(idx));
+                    }
+                } else {
+                    let from_buf = |ptr: *mut T| offset + mid + ptr.offset_from(b) as usize;
+                    ptr::copy_nonoverlapping(v.add(mid), b, len - mid);
+                    let mut hole = MergeHole {
+                        start: b,
+                        end: b.add(len - mid),
+                        dest: v.add(mid),
+                    };
+                    let mut left = v.add(mid);
+                    let mut out = v.add(len);
+                    while v < left && hole.start < hole.end {
+                        observer.compare(at(left.sub(1)), from_buf(hole.end.sub(1)));
+                        out = out.sub(1);
+                        if is_less(&*hole.end.sub(1), &*left.sub(1)) {
+                            left = left.sub(1);
+                            ptr::copy_nonoverlapping(left, out, 1);
+                        } else {
+                            hole.end = hole.end.sub(1);
+                            ptr::copy_nonoverlapping(hole.end, out, 1);
+                        }
+                        hole.dest = left;
+                        observer.write(at(out), &*out);
+                    }
+                    let remaining = hole.end.offset_from(hole.start) as usize;
+                    for idx in 0..remaining {
+                        observer.write(at(hole.dest) + idx, &*hole.start.add(idx));
+                    }
+                }
+            }
+        }
+
+        struct MergeHole<T> {
+            start: *mut T,
+            end: *mut T,
+            dest: *mut T,
+        }
+
+        impl<T> Drop for MergeHole<T> {
+            fn drop(&mut self) {
+                // Safety: start..end are the elements still held in the scratch buffer and dest
+                //         is the start of a gap of exactly that many slots in the merged slice
+                unsafe {
+                    let len = self.end.offset_from(self.start) as usize;
+                    ptr::copy_nonoverlapping(self.start, self.dest, len);
+                }
+            }
+        }
+
+        // Buffer-free merge: rotates the upper part of one run past the lower part of the other
+        // and recurses on both halves, giving O(n log n) moves per merge
+        fn merge_in_place<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            mid: usize,
+            is_less: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> bool,
+            O: Observer<T>,
+        {
+            let len = data.len();
+            if mid == 0 || mid == len {
+                return;
+            }
+            observer.compare(offset + mid - 1, offset + mid);
+            if !is_less(&data[mid], &data[mid - 1]) {
+                return;
+            }
+            if len == 2 {
+                data.swap(0, 1);
+                observer.swap(offset, offset + 1);
+                return;
+            }
+            let (cut_left, cut_right) = if mid >= len - mid {
+                // first item of the right run that is not less than data[cut_left]
+                let cut_left = mid / 2;
+                let (mut start, mut end) = (mid, len);
+                while start < end {
+                    let check_idx = start + (end - start) / 2;
+                    observer.compare(offset + check_idx, offset + cut_left);
+                    if is_less(&data[check_idx], &data[cut_left]) {
+                        start = check_idx + 1;
+                    } else {
+                        end = check_idx;
+                    }
+                }
+                (cut_left, start)
+            } else {
+                // first item of the left run that is greater than data[cut_right]
+                let cut_right = mid + (len - mid) / 2;
+                let (mut start, mut end) = (0, mid);
+                while start < end {
+                    let check_idx = start + (end - start) / 2;
+                    observer.compare(offset + cut_right, offset + check_idx);
+                    if is_less(&data[cut_right], &data[check_idx]) {
+                        end = check_idx;
+                    } else {
+                        start = check_idx + 1;
                     }
+                }
+                (start, cut_right)
+            };
+            data[cut_left..cut_right].rotate_left(mid - cut_left);
+            for (idx, item) in data[cut_left..cut_right].iter().enumerate() {
+                observer.write(offset + cut_left + idx, item);
+            }
+            let new_mid = cut_left + (cut_right - mid);
+            merge_in_place(&mut data[..new_mid], offset, cut_left, is_less, observer);
+            merge_in_place(
+                &mut data[new_mid..],
+                offset + new_mid,
+                mid - cut_left,
+                is_less,
+                observer,
+            );
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum PivotStrategy {
+            Last,
+            MedianOfThree,
+            Ninther,
+            Random { seed: u64 },
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct QuickSortConfig {
+            pub pivot: PivotStrategy,
+            pub three_way: bool,
+            pub insertion_cutoff: usize,
+        }
+
+        impl Default for QuickSortConfig {
+            fn default() -> Self {
+                Self {
+                    pivot: PivotStrategy::MedianOfThree,
+                    three_way: true,
+                    insertion_cutoff: 16,
+                }
+            }
+        }
+
+        pub fn quick_sort<T: Ord>(data: &mut [T]) {
+            quick_sort_by(data, T::cmp)
+        }
+        pub fn quick_sort_by<T, F>(data: &mut [T], compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            quick_sort_by_with(data, QuickSortConfig::default(), compare)
+        }
+        pub fn quick_sort_by_key<T, K, F>(data: &mut [T], mut key_of: F)
+        where
+            K: Ord,
+            F: FnMut(&T) -> K,
+        {
+            quick_sort_by(data, |a, b| key_of(a).cmp(&key_of(b)))
+        }
+        pub fn quick_sort_with<T: Ord>(data: &mut [T], config: QuickSortConfig) {
+            quick_sort_by_with(data, config, T::cmp)
+        }
+        pub fn quick_sort_by_with<T, F>(data: &mut [T], config: QuickSortConfig, mut compare: F)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+        {
+            quick_sort_observed(data, config, &mut compare, &mut ())
+        }
+        pub(crate) fn quick_sort_observed<T, F, O>(
+            data: &mut [T],
+            config: QuickSortConfig,
+            compare: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            let mut rng = XorShift64::new(match config.pivot {
+                PivotStrategy::Random { seed } => seed,
+                _ => 0,
+            });
+            let depth_limit = 2 * data.len().max(1).ilog2() as usize;
+            introsort(data, 0, &config, &mut rng, depth_limit, compare, observer);
+        }
+        fn introsort<T, F, O>(
+            mut data: &mut [T],
+            mut offset: usize,
+            config: &QuickSortConfig,
+            rng: &mut XorShift64,
+            mut depth_limit: usize,
+            compare: &mut F,
+            observer: &mut O,
+        ) where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            observer.enter();
+            // recurse into the smaller side and loop on the larger one so the stack stays O(log n)
+            loop {
+                if data.len() <= config.insertion_cutoff.max(1) {
+                    crate::insertion_sort_observed(data, offset, compare, observer);
+                    break;
+                }
+                if depth_limit == 0 {
+                    crate::heap_sort_observed(data, offset, compare, observer);
+                    break;
+                }
+                depth_limit -= 1;
+                let pivot = choose_pivot(data, offset, config.pivot, rng, compare, observer);
+                let (below, above) = if config.three_way {
+                    swap(data, offset, 0, pivot, observer);
+                    partition_three_way(data, offset, compare, observer)
+                } else {
+                    let last = data.len() - 1;
+                    swap(data, offset, pivot, last, observer);
+                    let pivot = partition(data, offset, compare, observer);
+                    (pivot, pivot + 1)
+                };
+                observer.partition_done(offset + below..offset + above);
+                let (left, rest) = data.split_at_mut(below);
+                let right = &mut rest[above - below..];
+                let right_offset = offset + above;
+                if left.len() < right.len() {
+                    introsort(left, offset, config, rng, depth_limit, compare, observer);
+                    data = right;
+                    offset = right_offset;
+                } else {
+                    introsort(
+                        right,
+                        right_offset,
+                        config,
+                        rng,
+                        depth_limit,
+                        compare,
+                        observer,
+                    );
+                    data = left;
+                }
+            }
+            observer.leave();
+        }
+        fn choose_pivot<T, F, O>(
+            data: &[T],
+            offset: usize,
+            strategy: PivotStrategy,
+            rng: &mut XorShift64,
+            compare: &mut F,
+            observer: &mut O,
+        ) -> usize
+        where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            let len = data.len();
+            let mid = len / 2;
+            let mut median = |a, b, c| median_of_three(data, offset, [a, b, c], compare, observer);
+            match strategy {
+                PivotStrategy::Last => len - 1,
+                PivotStrategy::Ninther if len >= 40 => {
+                    let step = len / 8;
+                    let low = median(0, step, 2 * step);
+                    let middle = median(mid - step, mid, mid + step);
+                    let high = median(len - 1 - 2 * step, len - 1 - step, len - 1);
+                    median(low, middle, high)
+                }
+                PivotStrategy::MedianOfThree | PivotStrategy::Ninther => median(0, mid, len - 1),
+                PivotStrategy::Random { .. } => (rng.next() % len as u64) as usize,
+            }
+        }
+        fn median_of_three<T, F, O>(
+            data: &[T],
+            offset: usize,
+            [a, b, c]: [usize; 3],
+            compare: &mut F,
+            observer: &mut O,
+        ) -> usize
+        where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            let mut is_less = |x: usize, y: usize| {
+                observer.compare(offset + x, offset + y);
+                compare(&data[x], &data[y]) == Ordering::Less
+            };
+            let ab = is_less(a, b);
+            let bc = is_less(b, c);
+            let ac = is_less(a, c);
+            if ab == bc {
+                b
+            } else if ab == ac {
+                c
+            } else {
+                a
+            }
+        }
+        // Lomuto partition around the pivot stored in the last slot; returns its final position
+        fn partition<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            compare: &mut F,
+            observer: &mut O,
+        ) -> usize
+        where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            let pivot = data.len() - 1;
+            let mut start_of_above = 0;
+            for current_compare in 0..pivot {
+                observer.compare(offset + current_compare, offset + pivot);
+                if compare(&data[current_compare], &data[pivot]) != Ordering::Greater {
+                    swap(data, offset, start_of_above, current_compare, observer);
                     start_of_above += 1;
                 }
-                current_compare += 1;
             }
-            if start_of_above < pivot {
-                data.swap(start_of_above, pivot);
-                pivot = start_of_above;
+            swap(data, offset, start_of_above, pivot, observer);
+            start_of_above
+        }
+        // Dijkstra partition around the pivot stored in the first slot; returns the bounds of
+        // the run of items equal to the pivot
+        pub(crate) fn partition_three_way<T, F, O>(
+            data: &mut [T],
+            offset: usize,
+            compare: &mut F,
+            observer: &mut O,
+        ) -> (usize, usize)
+        where
+            F: FnMut(&T, &T) -> Ordering,
+            O: Observer<T>,
+        {
+            let mut below = 0;
+            let mut current = 1;
+            let mut above = data.len();
+            // data[below] always holds an item equal to the pivot
+            while current < above {
+                observer.compare(offset + current, offset + below);
+                match compare(&data[current], &data[below]) {
+                    Ordering::Less => {
+                        swap(data, offset, below, current, observer);
+                        below += 1;
+                        current += 1;
+                    }
+                    Ordering::Greater => {
+                        above -= 1;
+                        swap(data, offset, current, above, observer);
+                    }
+                    Ordering::Equal => current += 1,
+                }
+            }
+            (below, above)
+        }
+        fn swap<T, O: Observer<T>>(
+            data: &mut [T],
+            offset: usize,
+            a: usize,
+            b: usize,
+            observer: &mut O,
+        ) {
+            if a != b {
+                data.swap(a, b);
+                observer.swap(offset + a, offset + b);
+            }
+        }
+
+        struct XorShift64(u64);
+
+        impl XorShift64 {
+            fn new(seed: u64) -> Self {
+                Self(if seed == 0 {
+                    0x9E37_79B9_7F4A_7C15
+                } else {
+                    seed
+                })
+            }
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+        }
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ParallelConfig {
+            pub threads: usize,
+            pub sequential_cutoff: usize,
+        }
+
+        impl Default for ParallelConfig {
+            fn default() -> Self {
+                Self {
+                    threads: thread::available_parallelism().map_or(1, |n| n.get()),
+                    sequential_cutoff: 1 << 13,
+                }
+            }
+        }
+
+        pub fn par_merge_sort<T: Ord + Send>(data: &mut [T]) {
+            par_merge_sort_by_with(data, ParallelConfig::default(), T::cmp)
+        }
+        pub fn par_merge_sort_with<T: Ord + Send>(data: &mut [T], config: ParallelConfig) {
+            par_merge_sort_by_with(data, config, T::cmp)
+        }
+        pub fn par_merge_sort_by_with<T, F>(data: &mut [T], config: ParallelConfig, compare: F)
+        where
+            T: Send,
+            F: Fn(&T, &T) -> Ordering + Sync,
+        {
+            if mem::size_of::<T>() == 0 {
+                return;
+            }
+            par_merge_sort_rec(data, &config, config.threads.max(1), &compare);
+        }
+        fn par_merge_sort_rec<T, F>(
+            data: &mut [T],
+            config: &ParallelConfig,
+            threads: usize,
+            compare: &F,
+        ) where
+            T: Send,
+            F: Fn(&T, &T) -> Ordering + Sync,
+        {
+            if threads <= 1 || data.len() <= config.sequential_cutoff.max(1) {
+                merge_sort_by_with(data, MergeStrategy::TopDown, compare);
+                return;
+            }
+            let mid = data.len() / 2;
+            let (left, right) = data.split_at_mut(mid);
+            let left_threads = threads / 2;
+            thread::scope(|scope| {
+                scope.spawn(|| par_merge_sort_rec(left, config, left_threads, compare));
+                par_merge_sort_rec(right, config, threads - left_threads, compare);
+            });
+            let mut buf = Vec::with_capacity(mid.min(data.len() - mid));
+            let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+            merge(data, 0, mid, &mut buf, &mut is_less, &mut ());
+        }
+
+        pub fn par_quick_sort<T: Ord + Send>(data: &mut [T]) {
+            par_quick_sort_by_with(
+                data,
+                QuickSortConfig::default(),
+                ParallelConfig::default(),
+                T::cmp,
+            )
+        }
+        pub fn par_quick_sort_with<T: Ord + Send>(data: &mut [T], parallel: ParallelConfig) {
+            par_quick_sort_by_with(data, QuickSortConfig::default(), parallel, T::cmp)
+        }
+        pub fn par_quick_sort_by_with<T, F>(
+            data: &mut [T],
+            config: QuickSortConfig,
+            parallel: ParallelConfig,
+            compare: F,
+        ) where
+            T: Send,
+            F: Fn(&T, &T) -> Ordering + Sync,
+        {
+            let seed = match config.pivot {
+                PivotStrategy::Random { seed } => seed,
+                _ => 0,
+            };
+            let depth_limit = 2 * data.len().max(1).ilog2() as usize;
+            par_introsort(
+                data,
+                &config,
+                &parallel,
+                parallel.threads.max(1),
+                XorShift64::new(seed),
+                depth_limit,
+                &compare,
+            );
+        }
+        fn par_introsort<T, F>(
+            data: &mut [T],
+            config: &QuickSortConfig,
+            parallel: &ParallelConfig,
+            threads: usize,
+            mut rng: XorShift64,
+            depth_limit: usize,
+            compare: &F,
+        ) where
+            T: Send,
+            F: Fn(&T, &T) -> Ordering + Sync,
+        {
+            let mut compare_mut = |a: &T, b: &T| compare(a, b);
+            if threads <= 1
+                || depth_limit == 0
+                || data.len()
+                    <= parallel
+                        .sequential_cutoff
+                        .max(config.insertion_cutoff)
+                        .max(1)
+            {
+                introsort(
+                    data,
+                    0,
+                    config,
+                    &mut rng,
+                    depth_limit,
+                    &mut compare_mut,
+                    &mut (),
+                );
+                return;
             }
-            pivot
+            let pivot = choose_pivot(data, 0, config.pivot, &mut rng, &mut compare_mut, &mut ());
+            let (below, above) = if config.three_way {
+                data.swap(0, pivot);
+                partition_three_way(data, 0, &mut compare_mut, &mut ())
+            } else {
+                let last = data.len() - 1;
+                data.swap(pivot, last);
+                let pivot = partition(data, 0, &mut compare_mut, &mut ());
+                (pivot, pivot + 1)
+            };
+            let len = data.len();
+            let (left, rest) = data.split_at_mut(below);
+            let right = &mut rest[above - below..];
+            // hand each side a share of the threads proportional to its size
+            let left_threads = (threads * left.len() / len).clamp(1, threads - 1);
+            let left_rng = XorShift64::new(rng.next());
+            thread::scope(|scope| {
+                scope.spawn(|| {
+                    par_introsort(
+                        left,
+                        config,
+                        parallel,
+                        left_threads,
+                        left_rng,
+                        depth_limit - 1,
+                        compare,
+                    )
+                });
+                par_introsort(
+                    right,
+                    config,
+                    parallel,
+                    threads - left_threads,
+                    rng,
+                    depth_limit - 1,
+                    compare,
+                );
+            });
         }
 
         pub fn merge_sort_2d<T: Ord + Copy>(data: &mut Vec2d<T>) {
@@ -1828,19 +4180,43 @@ pub mod divide_conquer {
     pub mod search {
         use std::cmp::Ordering;
 
+        use crate::instrument::Observer;
+
         pub fn binary_search<T: Ord>(find: &T, data: &[T]) -> Option<usize> {
+            binary_search_observed(find, data, 0, &mut ())
+        }
+        pub(crate) fn binary_search_observed<T: Ord, O: Observer<T>>(
+            find: &T,
+            data: &[T],
+            offset: usize,
+            observer: &mut O,
+        ) -> Option<usize> {
             if data.is_empty() {
                 return None;
             }
+            observer.enter();
             let mid = data.len() / 2;
-            match find.cmp(&data[mid]) {
+            observer.probe(offset + mid);
+            let rv = match find.cmp(&data[mid]) {
                 Ordering::Equal => Some(mid),
-                Ordering::Less => binary_search(find, &data[0..mid]),
-                Ordering::Greater => binary_search(find, &data[mid + 1..]).map(|m| mid + m + 1),
-            }
+                Ordering::Less => binary_search_observed(find, &data[0..mid], offset, observer),
+                Ordering::Greater => {
+                    binary_search_observed(find, &data[mid + 1..], offset + mid + 1, observer)
+                        .map(|m| mid + m + 1)
+                }
+            };
+            observer.leave();
+            rv
         }
 
         pub fn rotated_binary_search<T: Ord>(find: &T, data: &[T]) -> Option<usize> {
+            rotated_binary_search_observed(find, data, &mut ())
+        }
+        pub(crate) fn rotated_binary_search_observed<T: Ord, O: Observer<T>>(
+            find: &T,
+            data: &[T],
+            observer: &mut O,
+        ) -> Option<usize> {
             if data.is_empty() {
                 return None;
             }
@@ -1848,13 +4224,27 @@ pub mod divide_conquer {
             let mut e = data.len() - 1;
             while s <= e {
                 let mid = (e + s) / 2;
+                observer.probe(mid);
                 match find.cmp(&data[mid]) {
                     Ordering::Equal => return Some(mid),
-                    Ordering::Less if *find >= data[s] && mid > 0 => e = mid - 1,
-                    Ordering::Less => s = mid + 1,
-                    Ordering::Greater if *find <= data[e] => s = mid + 1,
-                    Ordering::Greater if mid > 0 => e = mid - 1,
-                    _ => return None,
+                    Ordering::Less => {
+                        observer.probe(s);
+                        if *find >= data[s] && mid > 0 {
+                            e = mid - 1;
+                        } else {
+                            s = mid + 1;
+                        }
+                    }
+                    Ordering::Greater => {
+                        observer.probe(e);
+                        if *find <= data[e] {
+                            s = mid + 1;
+                        } else if mid > 0 {
+                            e = mid - 1;
+                        } else {
+                            return None;
+                        }
+                    }
                 }
             }
             None
@@ -3651,7 +6041,10 @@ pub mod hash {
 }
 
 pub mod heap {
-    use std::collections::{BinaryHeap, HashMap};
+    use std::{
+        cmp::Reverse,
+        collections::{BinaryHeap, HashMap},
+    };
 
     pub fn maximum_product(inputs: &[u32]) -> u64 {
         let mut max_heap = BinaryHeap::new();
@@ -3703,6 +6096,106 @@ pub mod heap {
 
         result
     }
+
+    pub struct KWayMerge<I: Iterator> {
+        sources: Vec<I>,
+        heap: BinaryHeap<Reverse<(I::Item, usize)>>,
+    }
+
+    pub fn kway_merge<S>(sources: S) -> KWayMerge<<S::Item as IntoIterator>::IntoIter>
+    where
+        S: IntoIterator,
+        S::Item: IntoIterator,
+        <S::Item as IntoIterator>::Item: Ord,
+    {
+        let mut sources = sources
+            .into_iter()
+            .map(IntoIterator::into_iter)
+            .collect::<Vec<_>>();
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (idx, source) in sources.iter_mut().enumerate() {
+            if let Some(item) = source.next() {
+                heap.push(Reverse((item, idx)));
+            }
+        }
+        KWayMerge { sources, heap }
+    }
+
+    impl<I> KWayMerge<I>
+    where
+        I: Iterator,
+        I::Item: Ord,
+    {
+        pub fn dedup(self) -> KWayMergeDedup<I> {
+            KWayMergeDedup { merge: self }
+        }
+        fn peek(&self) -> Option<&I::Item> {
+            self.heap.peek().map(|Reverse((item, _))| item)
+        }
+    }
+
+    impl<I> Iterator for KWayMerge<I>
+    where
+        I: Iterator,
+        I::Item: Ord,
+    {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            // equal items are ordered by source index, so earlier sources win ties
+            let Reverse((item, idx)) = self.heap.pop()?;
+            if let Some(next) = self.sources[idx].next() {
+                self.heap.push(Reverse((next, idx)));
+            }
+            Some(item)
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.sources.iter().fold(
+                (self.heap.len(), Some(self.heap.len())),
+                |(low, high), source| {
+                    let (source_low, source_high) = source.size_hint();
+                    (
+                        low.saturating_add(source_low),
+                        high.zip(source_high).and_then(|(a, b)| a.checked_add(b)),
+                    )
+                },
+            )
+        }
+    }
+
+    pub struct KWayMergeDedup<I: Iterator> {
+        merge: KWayMerge<I>,
+    }
+
+    pub fn kway_merge_dedup<S>(sources: S) -> KWayMergeDedup<<S::Item as IntoIterator>::IntoIter>
+    where
+        S: IntoIterator,
+        S::Item: IntoIterator,
+        <S::Item as IntoIterator>::Item: Ord,
+    {
+        kway_merge(sources).dedup()
+    }
+
+    impl<I> Iterator for KWayMergeDedup<I>
+    where
+        I: Iterator,
+        I::Item: Ord,
+    {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            // keeps the item from the earliest source and drops its duplicates from every source
+            let item = self.merge.next()?;
+            while self.merge.peek() == Some(&item) {
+                self.merge.next();
+            }
+            Some(item)
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            let (low, high) = self.merge.size_hint();
+            (low.min(1), high)
+        }
+    }
 }
 
 pub mod graph {
@@ -3917,3 +6410,2645 @@ pub mod dynamic {
         coins_for_change(value_requested, coin_denominations, &mut memo)
     }
 }
+
+pub mod search_strategy {
+    use itertools::Itertools;
+
+    use crate::{
+        divide_conquer::search as dc_search,
+        print_2d::Vec2d,
+        search_2d::{staircase_find, staircase_find_all},
+    };
+
+    pub trait SearchStrategy<T> {
+        type Haystack: ?Sized;
+        type Position;
+
+        fn find(&self, key: &T, items: &Self::Haystack) -> Option<Self::Position>;
+        fn find_all(&self, key: &T, items: &Self::Haystack) -> Vec<Self::Position>;
+        fn contains(&self, key: &T, items: &Self::Haystack) -> bool {
+            self.find(key, items).is_some()
+        }
+    }
+
+    pub type SliceSearch<T> = dyn SearchStrategy<T, Haystack = [T], Position = usize>;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Linear;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Binary;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct RecursiveBinary;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct RotatedBinary;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Staircase;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Exponential;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Interpolation;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Jump;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Fibonacci;
+
+    impl<T: PartialEq> SearchStrategy<T> for Linear {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::linear_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| *item == key)
+                .map(|(idx, _)| idx)
+                .collect()
+        }
+    }
+
+    impl<T: PartialOrd> SearchStrategy<T> for Binary {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::binary_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: PartialOrd> SearchStrategy<T> for Exponential {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::exponential_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: Copy + PartialOrd + Into<f64>> SearchStrategy<T> for Interpolation {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::interpolation_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: PartialOrd> SearchStrategy<T> for Jump {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::jump_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: PartialOrd> SearchStrategy<T> for Fibonacci {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            crate::fibonacci_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: Ord> SearchStrategy<T> for RecursiveBinary {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            dc_search::binary_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            crate::equal_range(key, items).collect()
+        }
+    }
+
+    impl<T: Ord> SearchStrategy<T> for RotatedBinary {
+        type Haystack = [T];
+        type Position = usize;
+
+        fn find(&self, key: &T, items: &[T]) -> Option<usize> {
+            dc_search::rotated_binary_search(key, items)
+        }
+        fn find_all(&self, key: &T, items: &[T]) -> Vec<usize> {
+            let found = match self.find(key, items) {
+                Some(found) => found,
+                None => return Vec::new(),
+            };
+            // a run of equal keys may wrap around the rotation point
+            let len = items.len();
+            let mut start = found;
+            let mut count = 1;
+            while count < len && items[(start + len - 1) % len] == *key {
+                start = (start + len - 1) % len;
+                count += 1;
+            }
+            while count < len && items[(start + count) % len] == *key {
+                count += 1;
+            }
+            (0..count).map(|i| (start + i) % len).sorted().collect()
+        }
+    }
+
+    impl<T: Ord> SearchStrategy<T> for Staircase {
+        type Haystack = Vec2d<T>;
+        type Position = (usize, usize);
+
+        fn find(&self, key: &T, items: &Vec2d<T>) -> Option<(usize, usize)> {
+            staircase_find(items, key)
+        }
+        fn find_all(&self, key: &T, items: &Vec2d<T>) -> Vec<(usize, usize)> {
+            staircase_find_all(items, key)
+        }
+    }
+}
+
+pub mod radix {
+    use std::cmp::Ordering;
+
+    pub trait RadixKey {
+        fn key_len(&self) -> usize;
+        // most significant byte first; None once past the end of a shorter key
+        fn key_byte(&self, idx: usize) -> Option<u8>;
+    }
+
+    macro_rules! impl_radix_key_for_unsigned {
+        ($($t:ty),*) => {$(
+            impl RadixKey for $t {
+                fn key_len(&self) -> usize {
+                    std::mem::size_of::<$t>()
+                }
+                fn key_byte(&self, idx: usize) -> Option<u8> {
+                    self.to_be_bytes().get(idx).copied()
+                }
+            }
+        )*};
+    }
+
+    macro_rules! impl_radix_key_for_signed {
+        ($($t:ty => $u:ty),*) => {$(
+            impl RadixKey for $t {
+                fn key_len(&self) -> usize {
+                    std::mem::size_of::<$t>()
+                }
+                fn key_byte(&self, idx: usize) -> Option<u8> {
+                    // flipping the sign bit orders negative values before positive ones
+                    ((*self as $u) ^ (<$t>::MIN as $u)).to_be_bytes().get(idx).copied()
+                }
+            }
+        )*};
+    }
+
+    impl_radix_key_for_unsigned!(u8, u16, u32, u64, u128, usize);
+    impl_radix_key_for_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+    impl RadixKey for [u8] {
+        fn key_len(&self) -> usize {
+            self.len()
+        }
+        fn key_byte(&self, idx: usize) -> Option<u8> {
+            self.get(idx).copied()
+        }
+    }
+
+    impl RadixKey for Vec<u8> {
+        fn key_len(&self) -> usize {
+            self.len()
+        }
+        fn key_byte(&self, idx: usize) -> Option<u8> {
+            self.get(idx).copied()
+        }
+    }
+
+    impl RadixKey for str {
+        fn key_len(&self) -> usize {
+            self.len()
+        }
+        fn key_byte(&self, idx: usize) -> Option<u8> {
+            self.as_bytes().get(idx).copied()
+        }
+    }
+
+    impl RadixKey for String {
+        fn key_len(&self) -> usize {
+            self.len()
+        }
+        fn key_byte(&self, idx: usize) -> Option<u8> {
+            self.as_bytes().get(idx).copied()
+        }
+    }
+
+    impl<K: RadixKey + ?Sized> RadixKey for &K {
+        fn key_len(&self) -> usize {
+            (**self).key_len()
+        }
+        fn key_byte(&self, idx: usize) -> Option<u8> {
+            (**self).key_byte(idx)
+        }
+    }
+
+    const BUCKETS: usize = 257;
+    const MSD_INSERTION_CUTOFF: usize = 32;
+
+    pub fn counting_sort_by_key<T, F>(items: &mut [T], buckets: usize, mut key_of: F)
+    where
+        F: FnMut(&T) -> usize,
+    {
+        let keys = items.iter().map(&mut key_of).collect::<Vec<_>>();
+        let order = (0..items.len()).collect::<Vec<_>>();
+        let mut sorted_order = vec![0; items.len()];
+        counting_pass(&order, &mut sorted_order, buckets, |idx| keys[idx]);
+        apply_permutation(items, sorted_order);
+    }
+
+    pub fn lsd_radix_sort<T: RadixKey>(items: &mut [T]) {
+        let order = lsd_order(items);
+        apply_permutation(items, order);
+    }
+
+    pub fn lsd_radix_sort_by_key<T, K, F>(items: &mut [T], key_of: F)
+    where
+        K: RadixKey,
+        F: FnMut(&T) -> K,
+    {
+        let keys = items.iter().map(key_of).collect::<Vec<_>>();
+        apply_permutation(items, lsd_order(&keys));
+    }
+
+    pub fn msd_radix_sort<T: RadixKey>(items: &mut [T]) {
+        let order = msd_order(items);
+        apply_permutation(items, order);
+    }
+
+    pub fn msd_radix_sort_by_key<T, K, F>(items: &mut [T], key_of: F)
+    where
+        K: RadixKey,
+        F: FnMut(&T) -> K,
+    {
+        let keys = items.iter().map(key_of).collect::<Vec<_>>();
+        apply_permutation(items, msd_order(&keys));
+    }
+
+    // Stable counting sort of a list of item indices into out by the bucket of each index
+    fn counting_pass<F>(order: &[usize], out: &mut [usize], buckets: usize, mut bucket_of: F)
+    where
+        F: FnMut(usize) -> usize,
+    {
+        let mut starts = vec![0; buckets + 1];
+        for idx in order {
+            starts[bucket_of(*idx) + 1] += 1;
+        }
+        for bucket in 1..starts.len() {
+            starts[bucket] += starts[bucket - 1];
+        }
+        for idx in order {
+            let bucket = bucket_of(*idx);
+            out[starts[bucket]] = *idx;
+            starts[bucket] += 1;
+        }
+    }
+
+    fn bucket_of<K: RadixKey>(key: &K, depth: usize) -> usize {
+        key.key_byte(depth).map_or(0, |byte| byte as usize + 1)
+    }
+
+    fn lsd_order<K: RadixKey>(keys: &[K]) -> Vec<usize> {
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        let mut scratch = vec![0; keys.len()];
+        let max_len = keys.iter().map(RadixKey::key_len).max().unwrap_or(0);
+        for depth in (0..max_len).rev() {
+            let first_bucket = keys.first().map(|key| bucket_of(key, depth));
+            if keys
+                .iter()
+                .all(|key| Some(bucket_of(key, depth)) == first_bucket)
+            {
+                continue;
+            }
+            counting_pass(&order, &mut scratch, BUCKETS, |idx| {
+                bucket_of(&keys[idx], depth)
+            });
+            std::mem::swap(&mut order, &mut scratch);
+        }
+        order
+    }
+
+    fn msd_order<K: RadixKey>(keys: &[K]) -> Vec<usize> {
+        let mut order = (0..keys.len()).collect::<Vec<_>>();
+        let mut scratch = vec![0; keys.len()];
+        msd_order_rec(keys, &mut order, &mut scratch, 0);
+        order
+    }
+
+    fn msd_order_rec<K: RadixKey>(
+        keys: &[K],
+        order: &mut [usize],
+        scratch: &mut [usize],
+        depth: usize,
+    ) {
+        if order.len() <= MSD_INSERTION_CUTOFF {
+            crate::insertion_sort_by(order, |a, b| compare_from(&keys[*a], &keys[*b], depth));
+            return;
+        }
+        counting_pass(order, scratch, BUCKETS, |idx| bucket_of(&keys[idx], depth));
+        order.copy_from_slice(scratch);
+        // bucket 0 holds keys that have ended, which are all equal from here on
+        let mut start = 0;
+        while start < order.len() {
+            let bucket = bucket_of(&keys[order[start]], depth);
+            let mut end = start + 1;
+            while end < order.len() && bucket_of(&keys[order[end]], depth) == bucket {
+                end += 1;
+            }
+            if bucket != 0 {
+                msd_order_rec(
+                    keys,
+                    &mut order[start..end],
+                    &mut scratch[start..end],
+                    depth + 1,
+                );
+            }
+            start = end;
+        }
+    }
+
+    fn compare_from<K: RadixKey>(a: &K, b: &K, depth: usize) -> Ordering {
+        let len = a.key_len().max(b.key_len());
+        (depth..len)
+            .map(|idx| a.key_byte(idx).cmp(&b.key_byte(idx)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // Rearranges items so that items[i] ends up holding what was at items[order[i]]
+    fn apply_permutation<T>(items: &mut [T], order: Vec<usize>) {
+        let mut placed = vec![false; items.len()];
+        for start in 0..items.len() {
+            let mut current = start;
+            while !placed[current] {
+                placed[current] = true;
+                let next = order[current];
+                if next == start {
+                    break;
+                }
+                items.swap(current, next);
+                current = next;
+            }
+        }
+    }
+}
+
+pub mod instrument {
+    use std::{
+        cmp::Ordering,
+        ops::{Add, Range, Sub},
+    };
+
+    use crate::{
+        divide_conquer::{
+            search,
+            sort::{self, MergeStrategy, QuickSortConfig},
+        },
+        GapSequence,
+    };
+
+    // Hooks the sorts and searches call as they work; every method defaults to doing nothing, so
+    // the uninstrumented paths (which observe with ()) compile down to the plain algorithms
+    pub trait Observer<T> {
+        fn compare(&mut self, _a: usize, _b: usize) {}
+        // the key was compared against the item at idx
+        fn probe(&mut self, _idx: usize) {}
+        fn swap(&mut self, _a: usize, _b: usize) {}
+        fn write(&mut self, _idx: usize, _value: &T) {}
+        fn enter(&mut self) {}
+        fn leave(&mut self) {}
+        fn partition_done(&mut self, _pivot: Range<usize>) {}
+    }
+
+    impl<T> Observer<T> for () {}
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Stats {
+        pub comparisons: u64,
+        pub swaps: u64,
+        pub moves: u64,
+        pub max_depth: usize,
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        stats: Stats,
+        depth: usize,
+    }
+
+    impl<T> Observer<T> for Counter {
+        fn compare(&mut self, _a: usize, _b: usize) {
+            self.stats.comparisons += 1;
+        }
+        fn probe(&mut self, _idx: usize) {
+            self.stats.comparisons += 1;
+        }
+        fn swap(&mut self, _a: usize, _b: usize) {
+            self.stats.swaps += 1;
+        }
+        fn write(&mut self, _idx: usize, _value: &T) {
+            self.stats.moves += 1;
+        }
+        fn enter(&mut self) {
+            self.depth += 1;
+            self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        }
+        fn leave(&mut self) {
+            self.depth -= 1;
+        }
+    }
+
+    pub fn bubble_sort<T: Ord>(items: &mut [T]) -> Stats {
+        bubble_sort_by(items, T::cmp)
+    }
+    pub fn bubble_sort_by<T, F>(items: &mut [T], mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::bubble_sort_observed(items, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn insertion_sort<T: Ord>(items: &mut [T]) -> Stats {
+        insertion_sort_by(items, T::cmp)
+    }
+    pub fn insertion_sort_by<T, F>(items: &mut [T], mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::insertion_sort_observed(items, 0, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn selection_sort<T: Ord>(items: &mut [T]) -> Stats {
+        selection_sort_by(items, T::cmp)
+    }
+    pub fn selection_sort_by<T, F>(items: &mut [T], mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::selection_sort_observed(items, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn heap_sort<T: Ord>(items: &mut [T]) -> Stats {
+        heap_sort_by(items, T::cmp)
+    }
+    pub fn heap_sort_by<T, F>(items: &mut [T], mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::heap_sort_observed(items, 0, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn shell_sort<T: Ord>(items: &mut [T]) -> Stats {
+        shell_sort_by_with(items, GapSequence::default(), T::cmp)
+    }
+    pub fn shell_sort_with<T: Ord>(items: &mut [T], gaps: GapSequence) -> Stats {
+        shell_sort_by_with(items, gaps, T::cmp)
+    }
+    pub fn shell_sort_by_with<T, F>(items: &mut [T], gaps: GapSequence, mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::shell_sort_observed(items, gaps, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn tim_sort<T: Ord>(items: &mut [T]) -> Stats {
+        tim_sort_by(items, T::cmp)
+    }
+    pub fn tim_sort_by<T, F>(items: &mut [T], mut compare: F) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        crate::tim_sort_observed(items, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn bucket_sort<T: Copy + Into<f64>>(items: &mut [T]) -> Stats {
+        let mut counter = Counter::default();
+        crate::bucket_sort_observed(items, &mut counter);
+        counter.stats
+    }
+
+    pub fn counting_sort<T>(items: &mut [T]) -> Stats
+    where
+        T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T> + Into<usize> + From<usize>,
+    {
+        let mut counter = Counter::default();
+        crate::counting_sort_observed(items, &mut counter);
+        counter.stats
+    }
+
+    pub fn merge_sort<T: Ord>(items: &mut [T]) -> Stats {
+        merge_sort_by_with(items, MergeStrategy::default(), T::cmp)
+    }
+    pub fn merge_sort_with<T: Ord>(items: &mut [T], strategy: MergeStrategy) -> Stats {
+        merge_sort_by_with(items, strategy, T::cmp)
+    }
+    pub fn merge_sort_by_with<T, F>(
+        items: &mut [T],
+        strategy: MergeStrategy,
+        mut compare: F,
+    ) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        sort::merge_sort_observed(items, strategy, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn quick_sort<T: Ord>(items: &mut [T]) -> Stats {
+        quick_sort_by_with(items, QuickSortConfig::default(), T::cmp)
+    }
+    pub fn quick_sort_with<T: Ord>(items: &mut [T], config: QuickSortConfig) -> Stats {
+        quick_sort_by_with(items, config, T::cmp)
+    }
+    pub fn quick_sort_by_with<T, F>(
+        items: &mut [T],
+        config: QuickSortConfig,
+        mut compare: F,
+    ) -> Stats
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut counter = Counter::default();
+        sort::quick_sort_observed(items, config, &mut compare, &mut counter);
+        counter.stats
+    }
+
+    pub fn binary_search<T: PartialOrd>(key: &T, items: &[T]) -> (Option<usize>, Stats) {
+        let mut counter = Counter::default();
+        let rv = crate::binary_search_observed(key, items, &mut counter);
+        (rv, counter.stats)
+    }
+
+    pub fn recursive_binary_search<T: Ord>(key: &T, items: &[T]) -> (Option<usize>, Stats) {
+        let mut counter = Counter::default();
+        let rv = search::binary_search_observed(key, items, 0, &mut counter);
+        (rv, counter.stats)
+    }
+
+    pub fn rotated_binary_search<T: Ord>(key: &T, items: &[T]) -> (Option<usize>, Stats) {
+        let mut counter = Counter::default();
+        let rv = search::rotated_binary_search_observed(key, items, &mut counter);
+        (rv, counter.stats)
+    }
+}
+
+pub mod trace {
+    use std::{cmp::Ordering, ops::Range, vec};
+
+    use crate::{
+        divide_conquer::sort::{self, MergeStrategy, QuickSortConfig},
+        instrument::Observer,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum SortEvent<T> {
+        Compare(usize, usize),
+        Swap(usize, usize),
+        Write(usize, T),
+        PartitionDone(Range<usize>),
+    }
+
+    impl<T: Clone> SortEvent<T> {
+        /// Replays this event on `items`, which must be a copy of the input as it was
+        /// before the traced sort ran.
+        pub fn apply(&self, items: &mut [T]) {
+            match self {
+                SortEvent::Swap(a, b) => items.swap(*a, *b),
+                SortEvent::Write(idx, value) => items[*idx] = value.clone(),
+                SortEvent::Compare(..) | SortEvent::PartitionDone(..) => (),
+            }
+        }
+    }
+
+    /// The events a sort emitted, in order.
+    ///
+    /// The entry points in this module run the sort eagerly: by the time they return,
+    /// `items` is already sorted and every event is buffered here. To animate the sort,
+    /// keep a copy of the unsorted input and `SortEvent::apply` the events to it.
+    pub struct SortTrace<T> {
+        events: vec::IntoIter<SortEvent<T>>,
+    }
+
+    impl<T> Iterator for SortTrace<T> {
+        type Item = SortEvent<T>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.events.next()
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.events.size_hint()
+        }
+    }
+
+    impl<T> ExactSizeIterator for SortTrace<T> {}
+
+    struct Recorder<T> {
+        events: Vec<SortEvent<T>>,
+    }
+
+    impl<T> Recorder<T> {
+        fn new() -> Self {
+            Self { events: Vec::new() }
+        }
+        fn into_trace(self) -> SortTrace<T> {
+            SortTrace {
+                events: self.events.into_iter(),
+            }
+        }
+    }
+
+    impl<T: Clone> Observer<T> for Recorder<T> {
+        fn compare(&mut self, a: usize, b: usize) {
+            self.events.push(SortEvent::Compare(a, b));
+        }
+        fn swap(&mut self, a: usize, b: usize) {
+            self.events.push(SortEvent::Swap(a, b));
+        }
+        fn write(&mut self, idx: usize, value: &T) {
+            self.events.push(SortEvent::Write(idx, value.clone()));
+        }
+        fn partition_done(&mut self, pivot: Range<usize>) {
+            self.events.push(SortEvent::PartitionDone(pivot));
+        }
+    }
+
+    pub fn bubble_sort<T: Ord + Clone>(items: &mut [T]) -> SortTrace<T> {
+        bubble_sort_by(items, T::cmp)
+    }
+    pub fn bubble_sort_by<T, F>(items: &mut [T], mut compare: F) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut recorder = Recorder::new();
+        crate::bubble_sort_observed(items, &mut compare, &mut recorder);
+        recorder.into_trace()
+    }
+
+    pub fn insertion_sort<T: Ord + Clone>(items: &mut [T]) -> SortTrace<T> {
+        insertion_sort_by(items, T::cmp)
+    }
+    pub fn insertion_sort_by<T, F>(items: &mut [T], mut compare: F) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut recorder = Recorder::new();
+        crate::insertion_sort_observed(items, 0, &mut compare, &mut recorder);
+        recorder.into_trace()
+    }
+
+    pub fn selection_sort<T: Ord + Clone>(items: &mut [T]) -> SortTrace<T> {
+        selection_sort_by(items, T::cmp)
+    }
+    pub fn selection_sort_by<T, F>(items: &mut [T], mut compare: F) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut recorder = Recorder::new();
+        crate::selection_sort_observed(items, &mut compare, &mut recorder);
+        recorder.into_trace()
+    }
+
+    pub fn merge_sort<T: Ord + Clone>(items: &mut [T]) -> SortTrace<T> {
+        merge_sort_by_with(items, MergeStrategy::default(), T::cmp)
+    }
+    pub fn merge_sort_by<T, F>(items: &mut [T], compare: F) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        merge_sort_by_with(items, MergeStrategy::default(), compare)
+    }
+    pub fn merge_sort_with<T: Ord + Clone>(
+        items: &mut [T],
+        strategy: MergeStrategy,
+    ) -> SortTrace<T> {
+        merge_sort_by_with(items, strategy, T::cmp)
+    }
+    pub fn merge_sort_by_with<T, F>(
+        items: &mut [T],
+        strategy: MergeStrategy,
+        mut compare: F,
+    ) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut recorder = Recorder::new();
+        sort::merge_sort_observed(items, strategy, &mut compare, &mut recorder);
+        recorder.into_trace()
+    }
+
+    pub fn quick_sort<T: Ord + Clone>(items: &mut [T]) -> SortTrace<T> {
+        quick_sort_by_with(items, QuickSortConfig::default(), T::cmp)
+    }
+    pub fn quick_sort_by<T, F>(items: &mut [T], compare: F) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        quick_sort_by_with(items, QuickSortConfig::default(), compare)
+    }
+    pub fn quick_sort_with<T: Ord + Clone>(
+        items: &mut [T],
+        config: QuickSortConfig,
+    ) -> SortTrace<T> {
+        quick_sort_by_with(items, config, T::cmp)
+    }
+    pub fn quick_sort_by_with<T, F>(
+        items: &mut [T],
+        config: QuickSortConfig,
+        mut compare: F,
+    ) -> SortTrace<T>
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut recorder = Recorder::new();
+        sort::quick_sort_observed(items, config, &mut compare, &mut recorder);
+        recorder.into_trace()
+    }
+}
+
+pub mod external {
+    use std::{
+        cell::RefCell,
+        fs::{self, File},
+        io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write},
+        path::{Path, PathBuf},
+        process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{divide_conquer::sort::merge_sort, heap::kway_merge};
+
+    pub trait RecordCodec {
+        type Record;
+
+        fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Self::Record>>;
+        fn write_record<W: Write>(&self, writer: &mut W, record: &Self::Record) -> io::Result<()>;
+    }
+
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct LineCodec;
+
+    impl RecordCodec for LineCodec {
+        type Record = String;
+
+        fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<String>> {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Ok(Some(line))
+        }
+        fn write_record<W: Write>(&self, writer: &mut W, record: &String) -> io::Result<()> {
+            writer.write_all(record.as_bytes())?;
+            writer.write_all(b"\n")
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct FixedWidthCodec {
+        pub width: usize,
+    }
+
+    impl RecordCodec for FixedWidthCodec {
+        type Record = Vec<u8>;
+
+        fn read_record<R: BufRead>(&self, reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+            if self.width == 0 {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "record width must be non-zero",
+                ));
+            }
+            let mut record = vec![0; self.width];
+            let mut filled = 0;
+            while filled < self.width {
+                match reader.read(&mut record[filled..]) {
+                    Ok(0) => break,
+                    Ok(n) => filled += n,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            match filled {
+                0 => Ok(None),
+                n if n == self.width => Ok(Some(record)),
+                n => Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("trailing partial record of {n} bytes"),
+                )),
+            }
+        }
+        fn write_record<W: Write>(&self, writer: &mut W, record: &Vec<u8>) -> io::Result<()> {
+            if record.len() != self.width {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("record of {} bytes, expected {}", record.len(), self.width),
+                ));
+            }
+            writer.write_all(record)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct ExternalSortConfig {
+        // records, not bytes, held in memory and written out as one sorted run
+        pub chunk_len: usize,
+        // runs open at once while merging; more runs than this are merged over several passes
+        pub max_fan_in: usize,
+        pub temp_dir: PathBuf,
+    }
+
+    impl Default for ExternalSortConfig {
+        fn default() -> Self {
+            Self {
+                chunk_len: 1 << 16,
+                max_fan_in: 64,
+                temp_dir: std::env::temp_dir(),
+            }
+        }
+    }
+
+    pub fn external_sort<C, R, W>(
+        codec: &C,
+        input: R,
+        output: W,
+        config: &ExternalSortConfig,
+    ) -> io::Result<()>
+    where
+        C: RecordCodec,
+        C::Record: Ord,
+        R: Read,
+        W: Write,
+    {
+        let mut input = BufReader::new(input);
+        let mut output = BufWriter::new(output);
+        let chunk_len = config.chunk_len.max(1);
+        let mut runs = Vec::new();
+        loop {
+            let mut chunk = Vec::with_capacity(chunk_len.min(1 << 16));
+            while chunk.len() < chunk_len {
+                match codec.read_record(&mut input)? {
+                    Some(record) => chunk.push(record),
+                    None => break,
+                }
+            }
+            let exhausted = chunk.len() < chunk_len;
+            merge_sort(&mut chunk);
+            // everything fit in one chunk, so there is nothing to merge
+            if exhausted && runs.is_empty() {
+                for record in &chunk {
+                    codec.write_record(&mut output, record)?;
+                }
+                return output.flush();
+            }
+            if !chunk.is_empty() {
+                runs.push(RunFile::write(codec, &config.temp_dir, &chunk)?);
+            }
+            if exhausted {
+                break;
+            }
+        }
+        let max_fan_in = config.max_fan_in.max(2);
+        while runs.len() > max_fan_in {
+            let mut pending = runs.into_iter();
+            runs = Vec::new();
+            loop {
+                let group = pending.by_ref().take(max_fan_in).collect::<Vec<_>>();
+                if group.len() <= 1 {
+                    runs.extend(group);
+                    break;
+                }
+                let (run, mut writer) = RunFile::create(&config.temp_dir)?;
+                merge_runs(codec, &group, &mut writer)?;
+                writer.flush()?;
+                runs.push(run);
+            }
+        }
+        merge_runs(codec, &runs, &mut output)?;
+        output.flush()
+    }
+
+    fn merge_runs<C, W>(codec: &C, runs: &[RunFile], output: &mut W) -> io::Result<()>
+    where
+        C: RecordCodec,
+        C::Record: Ord,
+        W: Write,
+    {
+        let error = RefCell::new(None);
+        let sources = runs
+            .iter()
+            .map(|run| {
+                Ok(RunRecords {
+                    codec,
+                    reader: run.open()?,
+                    error: &error,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        for record in kway_merge(sources) {
+            codec.write_record(output, &record)?;
+        }
+        match error.into_inner() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    // ends early on a read error, leaving it for merge_runs to report once the merge stops
+    struct RunRecords<'a, C> {
+        codec: &'a C,
+        reader: BufReader<File>,
+        error: &'a RefCell<Option<io::Error>>,
+    }
+
+    impl<C: RecordCodec> Iterator for RunRecords<'_, C> {
+        type Item = C::Record;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.error.borrow().is_some() {
+                return None;
+            }
+            match self.codec.read_record(&mut self.reader) {
+                Ok(record) => record,
+                Err(e) => {
+                    *self.error.borrow_mut() = Some(e);
+                    None
+                }
+            }
+        }
+    }
+
+    struct RunFile {
+        path: PathBuf,
+    }
+
+    impl RunFile {
+        fn create(dir: &Path) -> io::Result<(Self, BufWriter<File>)> {
+            static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+            let id = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+            let path = dir.join(format!("ex11-run-{}-{id}.tmp", process::id()));
+            let writer = BufWriter::new(File::create_new(&path)?);
+            Ok((RunFile { path }, writer))
+        }
+        fn write<C: RecordCodec>(codec: &C, dir: &Path, records: &[C::Record]) -> io::Result<Self> {
+            let (run, mut writer) = Self::create(dir)?;
+            for record in records {
+                codec.write_record(&mut writer, record)?;
+            }
+            writer.flush()?;
+            Ok(run)
+        }
+        fn open(&self) -> io::Result<BufReader<File>> {
+            Ok(BufReader::new(File::open(&self.path)?))
+        }
+    }
+
+    impl Drop for RunFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+pub mod selection {
+    use std::{
+        cmp::{Ordering, Reverse},
+        collections::BinaryHeap,
+    };
+
+    use crate::divide_conquer::sort::{merge_sort, merge_sort_by, partition_three_way};
+
+    pub fn select_nth<T: Ord>(items: &mut [T], k: usize) -> Option<&T> {
+        select_nth_by(items, k, T::cmp)
+    }
+    pub fn select_nth_by<T, F>(items: &mut [T], k: usize, mut compare: F) -> Option<&T>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if k >= items.len() {
+            return None;
+        }
+        select_in_place(items, k, &mut compare);
+        Some(&items[k])
+    }
+    pub fn select_nth_by_key<T, K, F>(items: &mut [T], k: usize, mut key_of: F) -> Option<&T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        select_nth_by(items, k, |a, b| key_of(a).cmp(&key_of(b)))
+    }
+
+    // the lower median for even lengths
+    pub fn median<T: Ord>(items: &mut [T]) -> Option<&T> {
+        let k = items.len().saturating_sub(1) / 2;
+        select_nth(items, k)
+    }
+
+    pub fn top_k_smallest<T: Ord + Clone>(items: &[T], k: usize) -> Vec<T> {
+        let mut smallest = items.to_vec();
+        if k < smallest.len() {
+            select_in_place(&mut smallest, k, &mut T::cmp);
+            smallest.truncate(k);
+        }
+        merge_sort(&mut smallest);
+        smallest
+    }
+    pub fn top_k_largest<T: Ord + Clone>(items: &[T], k: usize) -> Vec<T> {
+        let mut largest = items.to_vec();
+        let mut descending = |a: &T, b: &T| b.cmp(a);
+        if k < largest.len() {
+            select_in_place(&mut largest, k, &mut descending);
+            largest.truncate(k);
+        }
+        merge_sort_by(&mut largest, descending);
+        largest
+    }
+
+    // Rearranges data so data[k] holds the item that sorting would put there, with nothing
+    // greater before it and nothing smaller after it
+    fn select_in_place<T, F>(mut data: &mut [T], mut k: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // quickselect on median-of-three pivots until a partition keeps more than three
+        // quarters of the range, then median of medians from there on for a linear bound
+        let mut guaranteed = false;
+        loop {
+            let len = data.len();
+            if len <= 5 {
+                crate::insertion_sort_by(data, &mut *compare);
+                return;
+            }
+            let pivot = if guaranteed {
+                median_of_medians(data, compare)
+            } else {
+                median_of_three(data, compare)
+            };
+            data.swap(0, pivot);
+            let (below, above) = partition_three_way(data, 0, compare, &mut ());
+            let kept = if k < below {
+                below
+            } else if k >= above {
+                len - above
+            } else {
+                return;
+            };
+            guaranteed |= kept * 4 > len * 3;
+            if k < below {
+                data = &mut data[..below];
+            } else {
+                data = &mut data[above..];
+                k -= above;
+            }
+        }
+    }
+    fn median_of_three<T, F>(data: &[T], compare: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (a, b, c) = (0, data.len() / 2, data.len() - 1);
+        let mut is_less = |x: usize, y: usize| compare(&data[x], &data[y]) == Ordering::Less;
+        let ab = is_less(a, b);
+        let bc = is_less(b, c);
+        let ac = is_less(a, c);
+        if ab == bc {
+            b
+        } else if ab == ac {
+            c
+        } else {
+            a
+        }
+    }
+    // Gathers the medians of groups of five at the front and selects their median
+    fn median_of_medians<T, F>(data: &mut [T], compare: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let groups = data.len().div_ceil(5);
+        for group in 0..groups {
+            let start = group * 5;
+            let end = (start + 5).min(data.len());
+            crate::insertion_sort_by(&mut data[start..end], &mut *compare);
+            data.swap(group, start + (end - start - 1) / 2);
+        }
+        let mid = (groups - 1) / 2;
+        select_in_place(&mut data[..groups], mid, compare);
+        mid
+    }
+
+    pub struct TopK<T> {
+        k: usize,
+        heap: BinaryHeap<T>,
+    }
+
+    impl<T: Ord> TopK<T> {
+        pub fn new(k: usize) -> Self {
+            Self {
+                k,
+                heap: BinaryHeap::with_capacity(k),
+            }
+        }
+        // keeps the k smallest items seen so far
+        pub fn push(&mut self, item: T) {
+            if self.heap.len() < self.k {
+                self.heap.push(item);
+            } else if let Some(mut largest) = self.heap.peek_mut() {
+                if item < *largest {
+                    *largest = item;
+                }
+            }
+        }
+        pub fn len(&self) -> usize {
+            self.heap.len()
+        }
+        pub fn is_empty(&self) -> bool {
+            self.heap.is_empty()
+        }
+        pub fn into_sorted_vec(self) -> Vec<T> {
+            self.heap.into_sorted_vec()
+        }
+    }
+
+    impl<T: Ord> Extend<T> for TopK<T> {
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            for item in iter {
+                self.push(item);
+            }
+        }
+    }
+
+    pub fn top_k_smallest_iter<I>(items: I, k: usize) -> Vec<I::Item>
+    where
+        I: IntoIterator,
+        I::Item: Ord,
+    {
+        let mut top = TopK::new(k);
+        top.extend(items);
+        top.into_sorted_vec()
+    }
+    pub fn top_k_largest_iter<I>(items: I, k: usize) -> Vec<I::Item>
+    where
+        I: IntoIterator,
+        I::Item: Ord,
+    {
+        let mut top = TopK::new(k);
+        top.extend(items.into_iter().map(Reverse));
+        top.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect()
+    }
+}
+
+pub mod max_subarray {
+    use std::{
+        collections::VecDeque,
+        ops::{Add, Mul, Range, Sub},
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct MaxSubarray<T> {
+        pub sum: T,
+        pub range: Range<usize>,
+    }
+
+    // range runs to the end of the items for a subarray that wraps, which then carries on
+    // with wrapped from the front; wrapped is 0..0 otherwise
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct CircularSubarray<T> {
+        pub sum: T,
+        pub range: Range<usize>,
+        pub wrapped: Range<usize>,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct MaxProduct<T> {
+        pub product: T,
+        pub range: Range<usize>,
+    }
+
+    // Kadane's algorithm over non-empty subarrays; on ties the one ending first wins
+    pub fn kadane<T>(items: &[T]) -> Option<MaxSubarray<T>>
+    where
+        T: Copy + Ord + Add<T, Output = T>,
+    {
+        extreme(items, |a, b| a > b)
+    }
+    fn extreme<T, F>(items: &[T], better: F) -> Option<MaxSubarray<T>>
+    where
+        T: Copy + Add<T, Output = T>,
+        F: Fn(T, T) -> bool,
+    {
+        let (first, rest) = items.split_first()?;
+        let (mut current, mut start) = (*first, 0);
+        let mut best = MaxSubarray {
+            sum: *first,
+            range: 0..1,
+        };
+        for (idx, item) in rest.iter().enumerate().map(|(idx, item)| (idx + 1, *item)) {
+            if better(item, current + item) {
+                (current, start) = (item, idx);
+            } else {
+                current = current + item;
+            }
+            if better(current, best.sum) {
+                best = MaxSubarray {
+                    sum: current,
+                    range: start..idx + 1,
+                };
+            }
+        }
+        Some(best)
+    }
+
+    pub fn circular<T>(items: &[T]) -> Option<CircularSubarray<T>>
+    where
+        T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        let straight = kadane(items)?;
+        let straight = CircularSubarray {
+            sum: straight.sum,
+            range: straight.range,
+            wrapped: 0..0,
+        };
+        let len = items.len();
+        let total = items[1..].iter().fold(items[0], |sum, item| sum + *item);
+        // the best wrapping subarray is everything except the smallest non-wrapping one
+        let Some(MaxSubarray { sum, range }) = extreme(items, |a, b| a < b) else {
+            return Some(straight);
+        };
+        if range.len() == len || total - sum <= straight.sum {
+            return Some(straight);
+        }
+        Some(CircularSubarray {
+            sum: total - sum,
+            range: range.end..len,
+            wrapped: 0..range.start,
+        })
+    }
+
+    pub fn max_product<T>(items: &[T]) -> Option<MaxProduct<T>>
+    where
+        T: Copy + Ord + Mul<T, Output = T>,
+    {
+        let (first, rest) = items.split_first()?;
+        // the largest and smallest products of subarrays ending at the current item, with
+        // where they start, since a negative item turns one into the other
+        let (mut high, mut low) = ((*first, 0), (*first, 0));
+        let mut best = MaxProduct {
+            product: *first,
+            range: 0..1,
+        };
+        for (idx, item) in rest.iter().enumerate().map(|(idx, item)| (idx + 1, *item)) {
+            let candidates = [(item, idx), (high.0 * item, high.1), (low.0 * item, low.1)];
+            high = *candidates.iter().max_by_key(|(value, _)| *value).unwrap();
+            low = *candidates.iter().min_by_key(|(value, _)| *value).unwrap();
+            if high.0 > best.product {
+                best = MaxProduct {
+                    product: high.0,
+                    range: high.1..idx + 1,
+                };
+            }
+        }
+        Some(best)
+    }
+
+    pub fn with_length<T>(items: &[T], min_len: usize, max_len: usize) -> Option<MaxSubarray<T>>
+    where
+        T: Copy + Default + Ord + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        let min_len = min_len.max(1);
+        let max_len = max_len.min(items.len());
+        if min_len > max_len {
+            return None;
+        }
+        let mut prefix_sums = Vec::with_capacity(items.len() + 1);
+        prefix_sums.push(T::default());
+        for item in items {
+            prefix_sums.push(*prefix_sums.last().unwrap() + *item);
+        }
+        // sliding window minimum over the prefix sums of the allowed starts
+        let mut starts = VecDeque::<usize>::new();
+        let mut best: Option<MaxSubarray<T>> = None;
+        for end in min_len..=items.len() {
+            let newest = end - min_len;
+            while starts
+                .back()
+                .is_some_and(|&start| prefix_sums[start] >= prefix_sums[newest])
+            {
+                starts.pop_back();
+            }
+            starts.push_back(newest);
+            if starts[0] + max_len < end {
+                starts.pop_front();
+            }
+            let sum = prefix_sums[end] - prefix_sums[starts[0]];
+            if best.as_ref().is_none_or(|best| sum > best.sum) {
+                best = Some(MaxSubarray {
+                    sum,
+                    range: starts[0]..end,
+                });
+            }
+        }
+        best
+    }
+
+    // Exactly k disjoint non-empty subarrays with the largest total, in order of position
+    pub fn k_non_overlapping<T>(items: &[T], k: usize) -> Option<Vec<MaxSubarray<T>>>
+    where
+        T: Copy + Default + Ord + Add<T, Output = T>,
+    {
+        let len = items.len();
+        if k > len {
+            return None;
+        }
+        // ending[j][i]: best total of j subarrays in items[..i] with the last ending at i - 1
+        // best[j][i]: best total of j subarrays anywhere in items[..i]
+        let mut ending = vec![vec![None; len + 1]; k + 1];
+        let mut best = vec![vec![None; len + 1]; k + 1];
+        let mut extends = vec![vec![false; len + 1]; k + 1];
+        best[0] = vec![Some(T::default()); len + 1];
+        for j in 1..=k {
+            for i in j..=len {
+                let extend = ending[j][i - 1];
+                let fresh = best[j - 1][i - 1];
+                let (previous, extended) = match (extend, fresh) {
+                    (Some(e), Some(f)) if e > f => (e, true),
+                    (_, Some(f)) => (f, false),
+                    (Some(e), None) => (e, true),
+                    (None, None) => continue,
+                };
+                extends[j][i] = extended;
+                ending[j][i] = Some(previous + items[i - 1]);
+                best[j][i] = match best[j][i - 1] {
+                    Some(skip) if skip >= ending[j][i].unwrap() => Some(skip),
+                    _ => ending[j][i],
+                };
+            }
+        }
+        let mut subarrays = Vec::with_capacity(k);
+        let mut i = len;
+        for j in (1..=k).rev() {
+            while best[j][i] != ending[j][i] {
+                i -= 1;
+            }
+            let end = i;
+            while extends[j][i] {
+                i -= 1;
+            }
+            i -= 1;
+            let sum = items[i + 1..end]
+                .iter()
+                .fold(items[i], |sum, item| sum + *item);
+            subarrays.push(MaxSubarray { sum, range: i..end });
+        }
+        subarrays.reverse();
+        Some(subarrays)
+    }
+}
+
+pub mod range_query {
+    use std::ops::{Add, Range, Sub};
+
+    use crate::matrix::Matrix2d;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PrefixSums<T> {
+        // prefix_sums[i] is the sum of the first i items
+        prefix_sums: Vec<T>,
+    }
+
+    impl<T> PrefixSums<T>
+    where
+        T: Copy + Default + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        pub fn new(items: &[T]) -> Self {
+            items.iter().copied().collect()
+        }
+        pub fn len(&self) -> usize {
+            self.prefix_sums.len() - 1
+        }
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn total(&self) -> T {
+            self.prefix_sums[self.len()]
+        }
+        pub fn prefix(&self, end: usize) -> T {
+            self.prefix_sums[end]
+        }
+        pub fn sum(&self, range: Range<usize>) -> T {
+            assert!(range.start <= range.end && range.end <= self.len());
+            self.prefix_sums[range.end] - self.prefix_sums[range.start]
+        }
+    }
+
+    impl<T> FromIterator<T> for PrefixSums<T>
+    where
+        T: Copy + Default + Add<T, Output = T>,
+    {
+        fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+            let mut prefix_sums = vec![T::default()];
+            for item in items {
+                prefix_sums.push(*prefix_sums.last().unwrap() + item);
+            }
+            Self { prefix_sums }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct FenwickTree<T> {
+        // 1-based; tree[i] holds the sum of the lowbit(i) items ending at item i - 1
+        tree: Vec<T>,
+    }
+
+    impl<T> FenwickTree<T>
+    where
+        T: Copy + Default + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        pub fn new(len: usize) -> Self {
+            Self {
+                tree: vec![T::default(); len + 1],
+            }
+        }
+        pub fn len(&self) -> usize {
+            self.tree.len() - 1
+        }
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn add(&mut self, idx: usize, delta: T) {
+            assert!(idx < self.len());
+            let mut node = idx + 1;
+            while node < self.tree.len() {
+                self.tree[node] = self.tree[node] + delta;
+                node += node & node.wrapping_neg();
+            }
+        }
+        pub fn set(&mut self, idx: usize, value: T) {
+            let delta = value - self.get(idx);
+            self.add(idx, delta);
+        }
+        pub fn get(&self, idx: usize) -> T {
+            self.sum(idx..idx + 1)
+        }
+        // sum of the first end items
+        pub fn prefix_sum(&self, end: usize) -> T {
+            assert!(end <= self.len());
+            let mut sum = T::default();
+            let mut node = end;
+            while node > 0 {
+                sum = sum + self.tree[node];
+                node &= node - 1;
+            }
+            sum
+        }
+        pub fn sum(&self, range: Range<usize>) -> T {
+            assert!(range.start <= range.end);
+            self.prefix_sum(range.end) - self.prefix_sum(range.start)
+        }
+    }
+
+    impl<T> From<&[T]> for FenwickTree<T>
+    where
+        T: Copy + Default + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        fn from(items: &[T]) -> Self {
+            let mut tree = vec![T::default()];
+            tree.extend_from_slice(items);
+            // push each node's partial sum up to its parent, for an O(n) build
+            for node in 1..tree.len() {
+                let parent = node + (node & node.wrapping_neg());
+                if parent < tree.len() {
+                    tree[parent] = tree[parent] + tree[node];
+                }
+            }
+            Self { tree }
+        }
+    }
+
+    // Range queries over any monoid given by an identity and an associative combine, with
+    // point updates and lazily propagated range assignment
+    pub struct SegmentTree<T, F> {
+        len: usize,
+        identity: T,
+        combine: F,
+        tree: Vec<T>,
+        pending: Vec<Option<T>>,
+    }
+
+    impl<T, F> SegmentTree<T, F>
+    where
+        T: Clone,
+        F: Fn(&T, &T) -> T,
+    {
+        pub fn new(items: &[T], identity: T, combine: F) -> Self {
+            let mut tree = Self {
+                len: items.len(),
+                tree: vec![identity.clone(); 4 * items.len().max(1)],
+                pending: vec![None; 4 * items.len().max(1)],
+                identity,
+                combine,
+            };
+            if !items.is_empty() {
+                tree.build(1, 0..items.len(), items);
+            }
+            tree
+        }
+        pub fn len(&self) -> usize {
+            self.len
+        }
+        pub fn is_empty(&self) -> bool {
+            self.len == 0
+        }
+        pub fn query(&self, range: Range<usize>) -> T {
+            assert!(range.start <= range.end && range.end <= self.len);
+            if range.is_empty() {
+                return self.identity.clone();
+            }
+            self.query_node(1, 0..self.len, &range)
+        }
+        pub fn set(&mut self, idx: usize, value: T) {
+            self.assign(idx..idx + 1, value);
+        }
+        pub fn assign(&mut self, range: Range<usize>, value: T) {
+            assert!(range.start <= range.end && range.end <= self.len);
+            if !range.is_empty() {
+                self.assign_node(1, 0..self.len, &range, &value);
+            }
+        }
+
+        fn build(&mut self, node: usize, span: Range<usize>, items: &[T]) {
+            if span.len() == 1 {
+                self.tree[node] = items[span.start].clone();
+                return;
+            }
+            let mid = span.start + span.len() / 2;
+            self.build(2 * node, span.start..mid, items);
+            self.build(2 * node + 1, mid..span.end, items);
+            self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+        fn query_node(&self, node: usize, span: Range<usize>, range: &Range<usize>) -> T {
+            if range.start <= span.start && span.end <= range.end {
+                return self.tree[node].clone();
+            }
+            let overlap = range.start.max(span.start)..range.end.min(span.end);
+            if let Some(value) = &self.pending[node] {
+                return self.repeat(value, overlap.len());
+            }
+            let mid = span.start + span.len() / 2;
+            if overlap.end <= mid {
+                self.query_node(2 * node, span.start..mid, range)
+            } else if overlap.start >= mid {
+                self.query_node(2 * node + 1, mid..span.end, range)
+            } else {
+                (self.combine)(
+                    &self.query_node(2 * node, span.start..mid, range),
+                    &self.query_node(2 * node + 1, mid..span.end, range),
+                )
+            }
+        }
+        fn assign_node(
+            &mut self,
+            node: usize,
+            span: Range<usize>,
+            range: &Range<usize>,
+            value: &T,
+        ) {
+            if range.end <= span.start || span.end <= range.start {
+                return;
+            }
+            if range.start <= span.start && span.end <= range.end {
+                self.tree[node] = self.repeat(value, span.len());
+                self.pending[node] = (span.len() > 1).then(|| value.clone());
+                return;
+            }
+            let mid = span.start + span.len() / 2;
+            if let Some(pending) = self.pending[node].take() {
+                self.assign_node(2 * node, span.start..mid, &(span.start..mid), &pending);
+                self.assign_node(2 * node + 1, mid..span.end, &(mid..span.end), &pending);
+            }
+            self.assign_node(2 * node, span.start..mid, range, value);
+            self.assign_node(2 * node + 1, mid..span.end, range, value);
+            self.tree[node] = (self.combine)(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+        // value combined with itself count times, by repeated doubling
+        fn repeat(&self, value: &T, mut count: usize) -> T {
+            let mut result = self.identity.clone();
+            let mut power = value.clone();
+            while count > 0 {
+                if count & 1 == 1 {
+                    result = (self.combine)(&result, &power);
+                }
+                count >>= 1;
+                if count > 0 {
+                    power = (self.combine)(&power, &power);
+                }
+            }
+            result
+        }
+    }
+
+    // Range queries over an idempotent combine (min, max, gcd, ...) in O(1) after an
+    // O(n log n) build; combine has the same in-place shape as QueryableMatrix2d's combiners
+    pub struct SparseTable<T, Op> {
+        // levels[k][i] is the combination of the 2^k items starting at item i
+        levels: Vec<Vec<T>>,
+        combine: Op,
+    }
+
+    impl<T, Op> SparseTable<T, Op>
+    where
+        T: Copy,
+        Op: Fn(&mut T, T),
+    {
+        pub fn new(items: &[T], combine: Op) -> Self {
+            let mut levels = vec![items.to_vec()];
+            let mut width = 1;
+            while 2 * width <= items.len() {
+                let previous = levels.last().unwrap();
+                let level = (0..=items.len() - 2 * width)
+                    .map(|idx| {
+                        let mut value = previous[idx];
+                        combine(&mut value, previous[idx + width]);
+                        value
+                    })
+                    .collect();
+                levels.push(level);
+                width *= 2;
+            }
+            Self { levels, combine }
+        }
+        pub fn len(&self) -> usize {
+            self.levels[0].len()
+        }
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+        pub fn query(&self, range: Range<usize>) -> Option<T> {
+            assert!(range.end <= self.len());
+            if range.is_empty() {
+                return None;
+            }
+            // two possibly overlapping blocks cover the range, which idempotence makes harmless
+            let level = range.len().ilog2() as usize;
+            let mut value = self.levels[level][range.start];
+            (self.combine)(&mut value, self.levels[level][range.end - (1 << level)]);
+            Some(value)
+        }
+    }
+
+    pub struct SparseTable2d<T, Op> {
+        // levels[k][l][(col, row)] is the combination of the 2^k by 2^l block at (col, row)
+        levels: Vec<Vec<Matrix2d<T>>>,
+        combine: Op,
+    }
+
+    impl<T, Op> SparseTable2d<T, Op>
+    where
+        T: Copy,
+        Op: Fn(&mut T, T),
+    {
+        pub fn new(matrix: &Matrix2d<T>, combine: Op) -> Self {
+            if matrix.size() == 0 {
+                return Self {
+                    levels: Vec::new(),
+                    combine,
+                };
+            }
+            let (width, height) = (matrix.width(), matrix.height());
+            let mut column = vec![matrix.clone()];
+            let mut block_height = 1;
+            while 2 * block_height <= height {
+                let previous = column.last().unwrap();
+                let mut level = Matrix2d::new(width, height + 1 - 2 * block_height, matrix[(0, 0)]);
+                for col in 0..level.width() {
+                    for row in 0..level.height() {
+                        let mut value = previous[(col, row)];
+                        combine(&mut value, previous[(col, row + block_height)]);
+                        level[(col, row)] = value;
+                    }
+                }
+                column.push(level);
+                block_height *= 2;
+            }
+            let mut levels = vec![column];
+            let mut block_width = 1;
+            while 2 * block_width <= width {
+                let column = levels
+                    .last()
+                    .unwrap()
+                    .iter()
+                    .map(|previous| {
+                        let mut level = Matrix2d::new(
+                            width + 1 - 2 * block_width,
+                            previous.height(),
+                            matrix[(0, 0)],
+                        );
+                        for col in 0..level.width() {
+                            for row in 0..level.height() {
+                                let mut value = previous[(col, row)];
+                                combine(&mut value, previous[(col + block_width, row)]);
+                                level[(col, row)] = value;
+                            }
+                        }
+                        level
+                    })
+                    .collect();
+                levels.push(column);
+                block_width *= 2;
+            }
+            Self { levels, combine }
+        }
+        // the combination of the submatrix from (col1, row1) to (col2, row2) inclusive
+        pub fn query(&self, col1: usize, row1: usize, col2: usize, row2: usize) -> T {
+            assert!(col1 <= col2 && row1 <= row2, "Empty submatrix");
+            let col_level = (col2 - col1 + 1).ilog2() as usize;
+            let row_level = (row2 - row1 + 1).ilog2() as usize;
+            let blocks = &self.levels[col_level][row_level];
+            let (col3, row3) = (col2 + 1 - (1 << col_level), row2 + 1 - (1 << row_level));
+            let mut value = blocks[(col1, row1)];
+            (self.combine)(&mut value, blocks[(col3, row1)]);
+            (self.combine)(&mut value, blocks[(col1, row3)]);
+            (self.combine)(&mut value, blocks[(col3, row3)]);
+            value
+        }
+    }
+}
+
+pub mod two_pointers {
+    use std::{
+        cmp::Ordering,
+        ops::{Add, Sub},
+    };
+
+    // Every function here expects items sorted ascending
+
+    // Distinct value pairs a <= b with a + b == target, in ascending order of a
+    pub fn pair_sums<T>(items: &[T], target: T) -> Vec<(T, T)>
+    where
+        T: Copy + Ord + Add<T, Output = T>,
+    {
+        let mut pairs = Vec::new();
+        let (mut lo, mut hi) = (0, items.len());
+        while lo + 1 < hi {
+            let (a, b) = (items[lo], items[hi - 1]);
+            match (a + b).cmp(&target) {
+                Ordering::Less => lo += 1,
+                Ordering::Greater => hi -= 1,
+                Ordering::Equal => {
+                    pairs.push((a, b));
+                    while lo < hi && items[lo] == a {
+                        lo += 1;
+                    }
+                    while lo < hi && items[hi - 1] == b {
+                        hi -= 1;
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    pub fn closest_pair_sum<T>(items: &[T], target: T) -> Option<(T, T)>
+    where
+        T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        let (mut lo, mut hi) = (0, items.len());
+        let mut best: Option<(T, (T, T))> = None;
+        while lo + 1 < hi {
+            let (a, b) = (items[lo], items[hi - 1]);
+            let sum = a + b;
+            let delta = distance(sum, target);
+            if best.is_none_or(|(best_delta, _)| delta < best_delta) {
+                best = Some((delta, (a, b)));
+            }
+            match sum.cmp(&target) {
+                Ordering::Less => lo += 1,
+                Ordering::Greater => hi -= 1,
+                Ordering::Equal => break,
+            }
+        }
+        best.map(|(_, pair)| pair)
+    }
+
+    // Distinct value triplets a <= b <= c with a + b + c == target
+    pub fn three_sums<T>(items: &[T], target: T) -> Vec<(T, T, T)>
+    where
+        T: Copy + Ord + Add<T, Output = T>,
+    {
+        let mut triplets = Vec::new();
+        for (i, &a) in items.iter().enumerate() {
+            if i > 0 && items[i - 1] == a {
+                continue;
+            }
+            let rest = &items[i + 1..];
+            let (mut lo, mut hi) = (0, rest.len());
+            while lo + 1 < hi {
+                let (b, c) = (rest[lo], rest[hi - 1]);
+                match (a + b + c).cmp(&target) {
+                    Ordering::Less => lo += 1,
+                    Ordering::Greater => hi -= 1,
+                    Ordering::Equal => {
+                        triplets.push((a, b, c));
+                        while lo < hi && rest[lo] == b {
+                            lo += 1;
+                        }
+                        while lo < hi && rest[hi - 1] == c {
+                            hi -= 1;
+                        }
+                    }
+                }
+            }
+        }
+        triplets
+    }
+
+    pub fn closest_three_sum<T>(items: &[T], target: T) -> Option<(T, T, T)>
+    where
+        T: Copy + Ord + Add<T, Output = T> + Sub<T, Output = T>,
+    {
+        let mut best: Option<(T, (T, T, T))> = None;
+        for (i, &a) in items.iter().enumerate() {
+            let rest = &items[i + 1..];
+            let (mut lo, mut hi) = (0, rest.len());
+            while lo + 1 < hi {
+                let (b, c) = (rest[lo], rest[hi - 1]);
+                let sum = a + b + c;
+                let delta = distance(sum, target);
+                if best.is_none_or(|(best_delta, _)| delta < best_delta) {
+                    best = Some((delta, (a, b, c)));
+                }
+                match sum.cmp(&target) {
+                    Ordering::Less => lo += 1,
+                    Ordering::Greater => hi -= 1,
+                    Ordering::Equal => return Some((a, b, c)),
+                }
+            }
+        }
+        best.map(|(_, triplet)| triplet)
+    }
+
+    // Distinct value quadruplets a <= b <= c <= d with a + b + c + d == target
+    pub fn four_sums<T>(items: &[T], target: T) -> Vec<(T, T, T, T)>
+    where
+        T: Copy + Ord + Add<T, Output = T>,
+    {
+        let mut quadruplets = Vec::new();
+        for (i, &a) in items.iter().enumerate() {
+            if i > 0 && items[i - 1] == a {
+                continue;
+            }
+            for (j, &b) in items.iter().enumerate().skip(i + 1) {
+                if j > i + 1 && items[j - 1] == b {
+                    continue;
+                }
+                let rest = &items[j + 1..];
+                let (mut lo, mut hi) = (0, rest.len());
+                while lo + 1 < hi {
+                    let (c, d) = (rest[lo], rest[hi - 1]);
+                    match (a + b + c + d).cmp(&target) {
+                        Ordering::Less => lo += 1,
+                        Ordering::Greater => hi -= 1,
+                        Ordering::Equal => {
+                            quadruplets.push((a, b, c, d));
+                            while lo < hi && rest[lo] == c {
+                                lo += 1;
+                            }
+                            while lo < hi && rest[hi - 1] == d {
+                                hi -= 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        quadruplets
+    }
+
+    // Number of index pairs i < j whose items differ by k (in either direction)
+    pub fn count_pairs_with_difference<T>(items: &[T], k: T) -> usize
+    where
+        T: Copy + Default + Ord + Sub<T, Output = T>,
+    {
+        let k = distance(k, T::default());
+        let runs = items
+            .chunk_by(|a, b| a == b)
+            .map(|run| (run[0], run.len()))
+            .collect::<Vec<_>>();
+        if k == T::default() {
+            return runs.iter().map(|(_, count)| count * (count - 1) / 2).sum();
+        }
+        let mut count = 0;
+        let mut lo = 0;
+        // runs[lo].0 never passes b, so subtracting it cannot underflow where a + k could
+        // overflow near T::MAX
+        for &(b, b_count) in &runs {
+            while b - runs[lo].0 > k {
+                lo += 1;
+            }
+            if b - runs[lo].0 == k {
+                count += runs[lo].1 * b_count;
+            }
+        }
+        count
+    }
+
+    // Number of index pairs i < j with items[i] + items[j] < target
+    pub fn count_pairs_sum_less_than<T>(items: &[T], target: T) -> usize
+    where
+        T: Copy + Ord + Add<T, Output = T>,
+    {
+        let (mut lo, mut hi) = (0, items.len());
+        let mut count = 0;
+        while lo + 1 < hi {
+            if items[lo] + items[hi - 1] < target {
+                // items[lo] pairs with everything up to hi - 1
+                count += hi - 1 - lo;
+                lo += 1;
+            } else {
+                hi -= 1;
+            }
+        }
+        count
+    }
+
+    fn distance<T>(a: T, b: T) -> T
+    where
+        T: Copy + Ord + Sub<T, Output = T>,
+    {
+        if a <= b {
+            b - a
+        } else {
+            a - b
+        }
+    }
+}
+
+pub mod grid {
+    use std::{
+        iter::{self, FusedIterator, StepBy},
+        ops::{Index, IndexMut, Range},
+        slice,
+    };
+
+    use bitvec::prelude::*;
+
+    pub use error::{GridError, GridErrorKind};
+
+    pub type Column<'a, T> = StepBy<slice::Iter<'a, T>>;
+    pub type ColumnMut<'a, T> = StepBy<slice::IterMut<'a, T>>;
+
+    // Row-major storage addressed by (col, row)
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct Grid<T> {
+        storage: Vec<T>,
+        width: usize,
+    }
+
+    impl<T> Grid<T> {
+        pub fn new(width: usize, height: usize, initialize_with: T) -> Self
+        where
+            T: Clone,
+        {
+            Self::try_new(width, height, initialize_with).expect("Grid size overflows usize")
+        }
+        pub fn try_new(width: usize, height: usize, initialize_with: T) -> Result<Self, GridError>
+        where
+            T: Clone,
+        {
+            let size = width
+                .checked_mul(height)
+                .ok_or(GridError::new(GridErrorKind::SizeOverflow))?;
+            Ok(Self {
+                storage: vec![initialize_with; size],
+                width,
+            })
+        }
+        pub fn width(&self) -> usize {
+            self.width
+        }
+        pub fn height(&self) -> usize {
+            if self.width == 0 {
+                0
+            } else {
+                self.storage.len() / self.width
+            }
+        }
+        pub fn size(&self) -> usize {
+            self.storage.len()
+        }
+        pub fn get(&self, col: usize, row: usize) -> Option<&T> {
+            if col < self.width && row < self.height() {
+                self.storage.get(row * self.width + col)
+            } else {
+                None
+            }
+        }
+        pub fn get_mut(&mut self, col: usize, row: usize) -> Option<&mut T> {
+            if col < self.width && row < self.height() {
+                self.storage.get_mut(row * self.width + col)
+            } else {
+                None
+            }
+        }
+        // neighbouring coordinates of (col, row), leaving out any that fall off the grid
+        pub fn neighbors(&self, col: usize, row: usize, connectivity: Connectivity) -> Neighbors {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height(), "Row Index out-of-range");
+            Neighbors {
+                center: (col, row),
+                width: self.width,
+                height: self.height(),
+                offsets: match connectivity {
+                    Connectivity::Four => &FOUR_CONNECTED,
+                    Connectivity::Eight => &EIGHT_CONNECTED,
+                },
+            }
+        }
+        pub fn as_slice(&self) -> &[T] {
+            &self.storage
+        }
+        pub fn as_mut_slice(&mut self) -> &mut [T] {
+            &mut self.storage
+        }
+        pub fn into_vec(self) -> Vec<T> {
+            self.storage
+        }
+        pub fn iter(&self) -> slice::Iter<'_, T> {
+            self.storage.iter()
+        }
+        pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+            self.storage.iter_mut()
+        }
+        pub fn row(&self, row: usize) -> &[T] {
+            assert!(row < self.height(), "Row Index out-of-range");
+            &self.storage[row * self.width..(row + 1) * self.width]
+        }
+        pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+            assert!(row < self.height(), "Row Index out-of-range");
+            &mut self.storage[row * self.width..(row + 1) * self.width]
+        }
+        pub fn rows(&self) -> slice::ChunksExact<'_, T> {
+            self.storage.chunks_exact(self.width.max(1))
+        }
+        pub fn rows_mut(&mut self) -> slice::ChunksExactMut<'_, T> {
+            self.storage.chunks_exact_mut(self.width.max(1))
+        }
+        pub fn column(&self, col: usize) -> Column<'_, T> {
+            assert!(col < self.width, "Column Index out-of-range");
+            // a grid with no rows has no cells past the first column either
+            self.storage
+                .get(col..)
+                .unwrap_or_default()
+                .iter()
+                .step_by(self.width)
+        }
+        pub fn column_mut(&mut self, col: usize) -> ColumnMut<'_, T> {
+            assert!(col < self.width, "Column Index out-of-range");
+            self.storage
+                .get_mut(col..)
+                .unwrap_or_default()
+                .iter_mut()
+                .step_by(self.width)
+        }
+        pub fn columns(
+            &self,
+        ) -> impl DoubleEndedIterator<Item = Column<'_, T>> + ExactSizeIterator {
+            (0..self.width).map(|col| self.column(col))
+        }
+        pub fn view(&self, cols: Range<usize>, rows: Range<usize>) -> GridView<'_, T> {
+            self.as_view().view(cols, rows)
+        }
+        pub fn view_mut(&mut self, cols: Range<usize>, rows: Range<usize>) -> GridViewMut<'_, T> {
+            let (width, height) = (self.width, self.height());
+            let (start, end) = window(width, width, height, &cols, &rows);
+            GridViewMut {
+                cells: &mut self.storage[start..end],
+                stride: width,
+                width: cols.len(),
+                height: rows.len(),
+            }
+        }
+        pub fn as_view(&self) -> GridView<'_, T> {
+            GridView {
+                cells: &self.storage,
+                stride: self.width,
+                width: self.width,
+                height: self.height(),
+            }
+        }
+        pub fn map<U, F>(&self, f: F) -> Grid<U>
+        where
+            F: FnMut(&T) -> U,
+        {
+            Grid {
+                storage: self.storage.iter().map(f).collect(),
+                width: self.width,
+            }
+        }
+        pub fn zip_with<U, V, F>(&self, other: &Grid<U>, mut f: F) -> Grid<V>
+        where
+            F: FnMut(&T, &U) -> V,
+        {
+            assert!(
+                self.width == other.width && self.height() == other.height(),
+                "Grid dimensions differ"
+            );
+            Grid {
+                storage: self
+                    .storage
+                    .iter()
+                    .zip(&other.storage)
+                    .map(|(a, b)| f(a, b))
+                    .collect(),
+                width: self.width,
+            }
+        }
+        pub fn transpose(&self) -> Grid<T>
+        where
+            T: Clone,
+        {
+            Grid {
+                storage: self.columns().flatten().cloned().collect(),
+                width: self.height(),
+            }
+        }
+        pub fn transpose_in_place(&mut self) {
+            let (width, height, size) = (self.width, self.height(), self.storage.len());
+            if width == height {
+                for row in 0..height {
+                    for col in row + 1..width {
+                        self.storage.swap(row * width + col, col * width + row);
+                    }
+                }
+            } else if size > 1 {
+                // the item at index i (other than the first and last) belongs at i * height
+                // mod (size - 1); follow each cycle of that permutation once
+                let destination =
+                    |idx: usize| (idx as u128 * height as u128 % (size as u128 - 1)) as usize;
+                let mut placed = bitvec![0; size];
+                for start in 1..size - 1 {
+                    if placed[start] {
+                        continue;
+                    }
+                    let mut current = start;
+                    loop {
+                        let next = destination(current);
+                        self.storage.swap(start, next);
+                        placed.set(next, true);
+                        if next == start {
+                            break;
+                        }
+                        current = next;
+                    }
+                }
+            }
+            self.width = height;
+        }
+        pub fn reduce_prefix_submatrices<U, InitU, CombinerUt, CombinerUu, RemoverUu>(
+            &self,
+            initializer_u: InitU,
+            combiner_ut: CombinerUt,
+            combiner_uu: CombinerUu,
+            remover_uu: RemoverUu,
+        ) -> Grid<U>
+        where
+            T: Copy,
+            U: Copy + Default,
+            InitU: Fn() -> U,
+            CombinerUt: Fn(&mut U, T),
+            CombinerUu: Fn(&mut U, U),
+            RemoverUu: Fn(&mut U, U),
+        {
+            let mut aux = Grid::new(self.width(), self.height(), U::default());
+            for col in 0..self.width() {
+                for row in 0..self.height() {
+                    let mut val = initializer_u();
+                    combiner_ut(&mut val, self[(col, row)]);
+                    if row > 0 {
+                        combiner_uu(&mut val, aux[(col, row - 1)]);
+                    }
+                    if col > 0 {
+                        combiner_uu(&mut val, aux[(col - 1, row)]);
+                    }
+                    if col > 0 && row > 0 {
+                        remover_uu(&mut val, aux[(col - 1, row - 1)]);
+                    }
+                    aux[(col, row)] = val;
+                }
+            }
+            aux
+        }
+    }
+
+    impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+        type Error = GridError;
+
+        fn try_from(values: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+            let width = values.first().map_or(0, Vec::len);
+            if width == 0 && !values.is_empty() {
+                return Err(GridError::new(GridErrorKind::ZeroWidth));
+            }
+            if let Some((row, found)) = values
+                .iter()
+                .map(Vec::len)
+                .enumerate()
+                .find(|(_, len)| *len != width)
+            {
+                return Err(GridError::new(GridErrorKind::RaggedRows {
+                    row,
+                    expected: width,
+                    found,
+                }));
+            }
+            Ok(Self {
+                storage: values.into_iter().flatten().collect(),
+                width,
+            })
+        }
+    }
+
+    impl<T> TryFrom<(usize, Vec<T>)> for Grid<T> {
+        type Error = GridError;
+
+        fn try_from((width, storage): (usize, Vec<T>)) -> Result<Self, Self::Error> {
+            if width == 0 {
+                return if storage.is_empty() {
+                    Ok(Self { storage, width })
+                } else {
+                    Err(GridError::new(GridErrorKind::ZeroWidth))
+                };
+            }
+            if storage.len() % width != 0 {
+                return Err(GridError::new(GridErrorKind::RaggedRows {
+                    row: storage.len() / width,
+                    expected: width,
+                    found: storage.len() % width,
+                }));
+            }
+            Ok(Self { storage, width })
+        }
+    }
+
+    impl<T> Index<(usize, usize)> for Grid<T> {
+        type Output = T;
+
+        fn index(&self, (col, row): (usize, usize)) -> &Self::Output {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height(), "Row Index out-of-range");
+            self.storage.index(row * self.width + col)
+        }
+    }
+
+    impl<T> IndexMut<(usize, usize)> for Grid<T> {
+        fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut Self::Output {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height(), "Row Index out-of-range");
+            self.storage.index_mut(row * self.width + col)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Connectivity {
+        Four,
+        Eight,
+    }
+
+    // (col, row) offsets, in row-major order
+    const FOUR_CONNECTED: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+    const EIGHT_CONNECTED: [(isize, isize); 8] = [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ];
+
+    #[derive(Debug, Clone)]
+    pub struct Neighbors {
+        center: (usize, usize),
+        width: usize,
+        height: usize,
+        offsets: &'static [(isize, isize)],
+    }
+
+    impl Iterator for Neighbors {
+        type Item = (usize, usize);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            while let Some(((d_col, d_row), rest)) = self.offsets.split_first() {
+                self.offsets = rest;
+                let col = self.center.0.checked_add_signed(*d_col);
+                let row = self.center.1.checked_add_signed(*d_row);
+                if let (Some(col), Some(row)) = (col, row) {
+                    if col < self.width && row < self.height {
+                        return Some((col, row));
+                    }
+                }
+            }
+            None
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (0, Some(self.offsets.len()))
+        }
+    }
+
+    impl FusedIterator for Neighbors {}
+
+    pub mod error {
+        use std::fmt;
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        pub struct GridError {
+            kind: GridErrorKind,
+        }
+
+        impl GridError {
+            pub fn new(kind: GridErrorKind) -> Self {
+                Self { kind }
+            }
+            pub fn kind(&self) -> &GridErrorKind {
+                &self.kind
+            }
+        }
+
+        #[derive(Debug, Clone, Eq, PartialEq)]
+        pub enum GridErrorKind {
+            RaggedRows {
+                row: usize,
+                expected: usize,
+                found: usize,
+            },
+            ZeroWidth,
+            SizeOverflow,
+        }
+
+        impl fmt::Display for GridError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.kind {
+                    GridErrorKind::RaggedRows {
+                        row,
+                        expected,
+                        found,
+                    } => write!(f, "row {row} has {found} items, expected {expected}"),
+                    GridErrorKind::ZeroWidth => write!(f, "rows must not be empty"),
+                    GridErrorKind::SizeOverflow => write!(f, "grid size overflows usize"),
+                }
+            }
+        }
+
+        impl std::error::Error for GridError {}
+    }
+
+    // Span of cells, from the top-left to just past the bottom-right of the window
+    fn window(
+        stride: usize,
+        width: usize,
+        height: usize,
+        cols: &Range<usize>,
+        rows: &Range<usize>,
+    ) -> (usize, usize) {
+        assert!(
+            cols.start <= cols.end && cols.end <= width,
+            "Column Range out-of-range"
+        );
+        assert!(
+            rows.start <= rows.end && rows.end <= height,
+            "Row Range out-of-range"
+        );
+        if cols.is_empty() || rows.is_empty() {
+            return (0, 0);
+        }
+        let start = rows.start * stride + cols.start;
+        (start, (rows.end - 1) * stride + cols.end)
+    }
+
+    // A borrowed rectangular window onto a grid
+    #[derive(Debug)]
+    pub struct GridView<'a, T> {
+        cells: &'a [T],
+        stride: usize,
+        width: usize,
+        height: usize,
+    }
+
+    // not derived, as that would needlessly require T: Clone
+    impl<T> Clone for GridView<'_, T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<T> Copy for GridView<'_, T> {}
+
+    impl<'a, T> GridView<'a, T> {
+        pub fn width(&self) -> usize {
+            self.width
+        }
+        pub fn height(&self) -> usize {
+            self.height
+        }
+        pub fn size(&self) -> usize {
+            self.width * self.height
+        }
+        pub fn row(&self, row: usize) -> &'a [T] {
+            assert!(row < self.height, "Row Index out-of-range");
+            // an empty window borrows no cells, whatever its height
+            let start = if self.width == 0 {
+                0
+            } else {
+                row * self.stride
+            };
+            &self.cells[start..start + self.width]
+        }
+        pub fn rows(&self) -> impl DoubleEndedIterator<Item = &'a [T]> + ExactSizeIterator {
+            let view = *self;
+            (0..self.height).map(move |row| view.row(row))
+        }
+        pub fn column(&self, col: usize) -> Column<'a, T> {
+            assert!(col < self.width, "Column Index out-of-range");
+            // an empty window borrows no cells, whatever its width
+            self.cells
+                .get(col..)
+                .unwrap_or_default()
+                .iter()
+                .step_by(self.stride)
+        }
+        pub fn columns(
+            &self,
+        ) -> impl DoubleEndedIterator<Item = Column<'a, T>> + ExactSizeIterator {
+            let view = *self;
+            (0..self.width).map(move |col| view.column(col))
+        }
+        pub fn view(&self, cols: Range<usize>, rows: Range<usize>) -> GridView<'a, T> {
+            let (start, end) = window(self.stride, self.width, self.height, &cols, &rows);
+            GridView {
+                cells: &self.cells[start..end],
+                stride: self.stride,
+                width: cols.len(),
+                height: rows.len(),
+            }
+        }
+        pub fn to_grid(&self) -> Grid<T>
+        where
+            T: Clone,
+        {
+            Grid {
+                storage: self.rows().flatten().cloned().collect(),
+                width: self.width,
+            }
+        }
+    }
+
+    impl<T> Index<(usize, usize)> for GridView<'_, T> {
+        type Output = T;
+
+        fn index(&self, (col, row): (usize, usize)) -> &Self::Output {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height, "Row Index out-of-range");
+            &self.cells[row * self.stride + col]
+        }
+    }
+
+    // A mutably borrowed rectangular window onto a grid
+    #[derive(Debug)]
+    pub struct GridViewMut<'a, T> {
+        cells: &'a mut [T],
+        stride: usize,
+        width: usize,
+        height: usize,
+    }
+
+    impl<T> GridViewMut<'_, T> {
+        pub fn width(&self) -> usize {
+            self.width
+        }
+        pub fn height(&self) -> usize {
+            self.height
+        }
+        pub fn size(&self) -> usize {
+            self.width * self.height
+        }
+        pub fn as_view(&self) -> GridView<'_, T> {
+            GridView {
+                cells: self.cells,
+                stride: self.stride,
+                width: self.width,
+                height: self.height,
+            }
+        }
+        pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+            assert!(row < self.height, "Row Index out-of-range");
+            // an empty window borrows no cells, whatever its height
+            let start = if self.width == 0 {
+                0
+            } else {
+                row * self.stride
+            };
+            &mut self.cells[start..start + self.width]
+        }
+        pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+            let width = self.width;
+            // a zero-width window borrows no cells, but still has its rows
+            let empty_rows = if width == 0 { self.height } else { 0 };
+            self.cells
+                .chunks_mut(self.stride.max(1))
+                .map(move |row| &mut row[..width])
+                .chain(iter::repeat_with(<&mut [T]>::default).take(empty_rows))
+        }
+        pub fn column_mut(&mut self, col: usize) -> ColumnMut<'_, T> {
+            assert!(col < self.width, "Column Index out-of-range");
+            self.cells
+                .get_mut(col..)
+                .unwrap_or_default()
+                .iter_mut()
+                .step_by(self.stride)
+        }
+        pub fn fill(&mut self, value: T)
+        where
+            T: Clone,
+        {
+            for row in self.rows_mut() {
+                row.fill(value.clone());
+            }
+        }
+    }
+
+    impl<T> Index<(usize, usize)> for GridViewMut<'_, T> {
+        type Output = T;
+
+        fn index(&self, (col, row): (usize, usize)) -> &Self::Output {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height, "Row Index out-of-range");
+            &self.cells[row * self.stride + col]
+        }
+    }
+
+    impl<T> IndexMut<(usize, usize)> for GridViewMut<'_, T> {
+        fn index_mut(&mut self, (col, row): (usize, usize)) -> &mut Self::Output {
+            assert!(col < self.width, "Column Index out-of-range");
+            assert!(row < self.height, "Row Index out-of-range");
+            &mut self.cells[row * self.stride + col]
+        }
+    }
+}
@@ -1,3 +1,208 @@
+// -0.0 == 0.0, so the signed zeroes are told apart by their bits
+fn assert_sorts_floats_with_total_cmp<F>(sort: F)
+where
+    F: Fn(&mut [f64]),
+{
+    let mut input = vec![2.5, -1.0, 0.0, -0.0, 10.25, 3.0];
+    sort(&mut input);
+    assert_eq!(vec![-1.0, -0.0, 0.0, 2.5, 3.0, 10.25], input);
+    assert!(input[1].is_sign_negative() && input[2].is_sign_positive());
+
+    let mut input = vec![0.0, -0.0, 0.0, -0.0];
+    sort(&mut input);
+    assert_eq!(
+        vec![
+            (-0.0_f64).to_bits(),
+            (-0.0_f64).to_bits(),
+            0.0_f64.to_bits(),
+            0.0_f64.to_bits()
+        ],
+        input.iter().map(|item| item.to_bits()).collect::<Vec<_>>()
+    );
+
+    // total_cmp puts negative NaN below -inf and positive NaN above +inf
+    let mut input = vec![f64::NAN, 1.0, f64::INFINITY, -f64::NAN, f64::NEG_INFINITY];
+    sort(&mut input);
+    assert!(input[0].is_nan() && input[0].is_sign_negative());
+    assert_eq!(vec![f64::NEG_INFINITY, 1.0, f64::INFINITY], input[1..4]);
+    assert!(input[4].is_nan() && input[4].is_sign_positive());
+}
+
+// Empty, single-line, square and rectangular shapes, as (width, height)
+const GRID_SHAPES: [(usize, usize); 10] = [
+    (0, 0),
+    (1, 1),
+    (1, 6),
+    (6, 1),
+    (2, 3),
+    (3, 8),
+    (8, 3),
+    (5, 5),
+    (4, 6),
+    (6, 10),
+];
+
+// 1, 2, 3, ... in row-major order, so every cell is told apart by its value
+fn numbered_grid(width: usize, height: usize) -> crate::print_2d::Vec2d<usize> {
+    crate::print_2d::Vec2d::try_from((width, (1..=width * height).collect::<Vec<_>>())).unwrap()
+}
+
+fn random_data(seed: u64, len: usize, max: u32) -> Vec<u32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) % (max as u64 + 1)) as u32
+        })
+        .collect()
+}
+
+mod bounds {
+    use super::super::{
+        equal_range, equal_range_by, equal_range_by_key, lower_bound, lower_bound_by_key,
+        upper_bound, upper_bound_by_key,
+    };
+
+    const DATA: [u32; 10] = [1, 3, 3, 3, 5, 8, 8, 13, 21, 21];
+
+    #[test]
+    fn lower_and_upper_bound_return_insertion_points() {
+        assert_eq!(0, lower_bound(&0, &DATA));
+        assert_eq!(0, upper_bound(&0, &DATA));
+        assert_eq!(1, lower_bound(&3, &DATA));
+        assert_eq!(4, upper_bound(&3, &DATA));
+        assert_eq!(5, lower_bound(&6, &DATA));
+        assert_eq!(5, upper_bound(&6, &DATA));
+        assert_eq!(8, lower_bound(&21, &DATA));
+        assert_eq!(10, upper_bound(&21, &DATA));
+        assert_eq!(10, lower_bound(&22, &DATA));
+        assert_eq!(0, lower_bound(&5, &[0_u32; 0]));
+    }
+
+    #[test]
+    fn equal_range_spans_all_duplicates() {
+        assert_eq!(1..4, equal_range(&3, &DATA));
+        assert_eq!(5..7, equal_range(&8, &DATA));
+        assert_eq!(4..5, equal_range(&5, &DATA));
+        assert_eq!(7..7, equal_range(&9, &DATA));
+        assert!(equal_range(&9, &DATA).is_empty());
+    }
+
+    #[test]
+    fn keys_that_cannot_be_compared_find_an_empty_range() {
+        use super::super::search_strategy::{Binary, SearchStrategy};
+
+        let readings = [-1.5, 0.0, 0.0, 2.5, f64::NAN];
+        assert_eq!(0..0, equal_range(&f64::NAN, &readings));
+        assert_eq!(0, lower_bound(&f64::NAN, &readings));
+        assert_eq!(0, upper_bound(&f64::NAN, &readings));
+        assert!(Binary.find_all(&f64::NAN, &readings[..]).is_empty());
+        assert_eq!(1..3, equal_range(&0.0, &readings));
+        assert_eq!(4, upper_bound(&2.5, &readings));
+    }
+
+    #[test]
+    fn comparator_variant_supports_descending_order() {
+        let descending = [9, 7, 7, 7, 4, 1];
+        assert_eq!(1..4, equal_range_by(&descending, |item| 7.cmp(item)));
+        assert_eq!(4..4, equal_range_by(&descending, |item| 5.cmp(item)));
+    }
+
+    #[test]
+    fn key_variants_query_records_by_field() {
+        let logs = [
+            (100, "boot"),
+            (105, "login"),
+            (105, "open"),
+            (105, "close"),
+            (230, "logout"),
+        ];
+        assert_eq!(1, lower_bound_by_key(&105, &logs, |(ts, _)| *ts));
+        assert_eq!(4, upper_bound_by_key(&105, &logs, |(ts, _)| *ts));
+        assert_eq!(
+            vec!["login", "open", "close"],
+            logs[equal_range_by_key(&105, &logs, |(ts, _)| *ts)]
+                .iter()
+                .map(|(_, msg)| *msg)
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+mod search_variants {
+    use super::super::{
+        exponential_search, exponential_search_unbounded, fibonacci_search, interpolation_search,
+        jump_search, linear_search,
+    };
+
+    fn random_sorted_data(seed: u64, len: usize, max: u32) -> Vec<u32> {
+        let mut data = super::random_data(seed, len, max);
+        data.sort_unstable();
+        data
+    }
+
+    fn assert_agrees_with_linear_search(search: fn(&u32, &[u32]) -> Option<usize>) {
+        for seed in 0..200 {
+            let data = random_sorted_data(seed, seed as usize % 40, 60);
+            for key in 0..=61 {
+                match search(&key, &data) {
+                    Some(idx) => assert_eq!(key, data[idx], "seed {seed}, data {data:?}"),
+                    None => assert_eq!(
+                        None,
+                        linear_search(&key, &data),
+                        "seed {seed}, key {key}, data {data:?}"
+                    ),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exponential_search_agrees_with_linear_search_on_random_input() {
+        assert_agrees_with_linear_search(exponential_search);
+    }
+
+    #[test]
+    fn interpolation_search_agrees_with_linear_search_on_random_input() {
+        assert_agrees_with_linear_search(interpolation_search);
+    }
+
+    #[test]
+    fn jump_search_agrees_with_linear_search_on_random_input() {
+        assert_agrees_with_linear_search(jump_search);
+    }
+
+    #[test]
+    fn fibonacci_search_agrees_with_linear_search_on_random_input() {
+        assert_agrees_with_linear_search(fibonacci_search);
+    }
+
+    #[test]
+    fn exponential_search_works_over_an_unbounded_source() {
+        let squares = |idx: usize| Some(idx * idx);
+        assert_eq!(
+            Some(1234),
+            exponential_search_unbounded(&(1234 * 1234), squares)
+        );
+        assert_eq!(
+            None,
+            exponential_search_unbounded(&(1234 * 1234 + 1), squares)
+        );
+        assert_eq!(Some(0), exponential_search_unbounded(&0, squares));
+    }
+
+    #[test]
+    fn exponential_search_works_over_a_streaming_source_that_ends() {
+        let stream = [2, 4, 8, 16, 32];
+        let source = |idx: usize| stream.get(idx).copied();
+        assert_eq!(Some(3), exponential_search_unbounded(&16, source));
+        assert_eq!(None, exponential_search_unbounded(&64, source));
+        assert_eq!(None, exponential_search_unbounded(&1, source));
+    }
+}
+
 mod rotate {
     use super::super::rotate;
 
@@ -91,7 +296,7 @@ mod rotate {
 }
 
 mod bubble_sort {
-    use super::super::bubble_sort;
+    use super::super::{bubble_sort, bubble_sort_by, bubble_sort_by_key};
 
     fn test_data(size: usize) -> Vec<u32> {
         vec![
@@ -150,10 +355,43 @@ mod bubble_sort {
             input
         );
     }
+
+    #[test]
+    fn it_sorts_descending_with_a_comparator() {
+        let mut input = test_data(20);
+        bubble_sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(
+            vec![200, 133, 101, 97, 92, 87, 12, 12, 11, 10, 9, 7, 6, 5, 5, 3, 2, 1, 1, 0,],
+            input
+        );
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| bubble_sort_by(items, f64::total_cmp));
+    }
+
+    #[test]
+    fn it_sorts_non_copy_records_by_a_field() {
+        let mut input = vec![
+            (String::from("pear"), 3),
+            (String::from("apple"), 7),
+            (String::from("fig"), 1),
+        ];
+        bubble_sort_by_key(&mut input, |(_, cost)| *cost);
+        assert_eq!(
+            vec![
+                (String::from("fig"), 1),
+                (String::from("pear"), 3),
+                (String::from("apple"), 7),
+            ],
+            input
+        );
+    }
 }
 
 mod insertion_sort {
-    use super::super::insertion_sort;
+    use super::super::{insertion_sort, insertion_sort_by, insertion_sort_by_key};
 
     fn test_data(size: usize) -> Vec<u32> {
         vec![
@@ -212,10 +450,43 @@ mod insertion_sort {
             input
         );
     }
+
+    #[test]
+    fn it_sorts_descending_with_a_comparator() {
+        let mut input = test_data(20);
+        insertion_sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(
+            vec![200, 133, 101, 97, 92, 87, 12, 12, 11, 10, 9, 7, 6, 5, 5, 3, 2, 1, 1, 0,],
+            input
+        );
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| insertion_sort_by(items, f64::total_cmp));
+    }
+
+    #[test]
+    fn it_sorts_non_copy_records_by_a_field() {
+        let mut input = vec![
+            (String::from("pear"), 3),
+            (String::from("apple"), 7),
+            (String::from("fig"), 1),
+        ];
+        insertion_sort_by_key(&mut input, |(_, cost)| *cost);
+        assert_eq!(
+            vec![
+                (String::from("fig"), 1),
+                (String::from("pear"), 3),
+                (String::from("apple"), 7),
+            ],
+            input
+        );
+    }
 }
 
 mod selection_sort {
-    use super::super::selection_sort;
+    use super::super::{selection_sort, selection_sort_by, selection_sort_by_key};
 
     fn test_data(size: usize) -> Vec<u32> {
         vec![
@@ -274,6 +545,39 @@ mod selection_sort {
             input
         );
     }
+
+    #[test]
+    fn it_sorts_descending_with_a_comparator() {
+        let mut input = test_data(20);
+        selection_sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(
+            vec![200, 133, 101, 97, 92, 87, 12, 12, 11, 10, 9, 7, 6, 5, 5, 3, 2, 1, 1, 0,],
+            input
+        );
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| selection_sort_by(items, f64::total_cmp));
+    }
+
+    #[test]
+    fn it_sorts_non_copy_records_by_a_field() {
+        let mut input = vec![
+            (String::from("pear"), 3),
+            (String::from("apple"), 7),
+            (String::from("fig"), 1),
+        ];
+        selection_sort_by_key(&mut input, |(_, cost)| *cost);
+        assert_eq!(
+            vec![
+                (String::from("fig"), 1),
+                (String::from("pear"), 3),
+                (String::from("apple"), 7),
+            ],
+            input
+        );
+    }
 }
 
 mod counting_sort {
@@ -338,6 +642,317 @@ mod counting_sort {
     }
 }
 
+mod heap_sort {
+    use super::super::{heap_sort, heap_sort_by, heap_sort_by_key};
+    use super::random_data;
+
+    #[test]
+    fn it_sorts_random_input() {
+        for seed in 0..30 {
+            let mut input = random_data(seed, seed as usize * 11, 100);
+            let mut expected = input.clone();
+            expected.sort();
+            heap_sort(&mut input);
+            assert_eq!(expected, input);
+        }
+    }
+
+    #[test]
+    fn it_sorts_records_by_a_field() {
+        let mut input = vec![("c", 3), ("a", 1), ("d", 4), ("b", 2)];
+        heap_sort_by_key(&mut input, |(_, rank)| *rank);
+        assert_eq!(vec![("a", 1), ("b", 2), ("c", 3), ("d", 4)], input);
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| heap_sort_by(items, f64::total_cmp));
+    }
+}
+
+mod shell_sort {
+    use super::super::{shell_sort, shell_sort_by, shell_sort_with, GapSequence};
+    use super::random_data;
+
+    #[test]
+    fn every_gap_sequence_sorts_random_input() {
+        for gaps in [
+            GapSequence::Shell,
+            GapSequence::Knuth,
+            GapSequence::Ciura,
+            GapSequence::Sedgewick,
+        ] {
+            for seed in 0..30 {
+                let mut input = random_data(seed, seed as usize * 37, 1000);
+                let mut expected = input.clone();
+                expected.sort();
+                shell_sort_with(&mut input, gaps);
+                assert_eq!(expected, input, "{gaps:?} with seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn gap_sequences_are_descending_end_in_one_and_stay_below_the_length() {
+        assert_eq!(vec![50, 25, 12, 6, 3, 1], GapSequence::Shell.gaps(100));
+        assert_eq!(vec![40, 13, 4, 1], GapSequence::Knuth.gaps(100));
+        assert_eq!(vec![57, 23, 10, 4, 1], GapSequence::Ciura.gaps(100));
+        assert_eq!(vec![77, 23, 8, 1], GapSequence::Sedgewick.gaps(100));
+        assert_eq!(vec![1], GapSequence::Ciura.gaps(2));
+    }
+
+    #[test]
+    fn it_sorts_with_a_comparator() {
+        let mut input = vec![3, 1, 2];
+        shell_sort(&mut input);
+        assert_eq!(vec![1, 2, 3], input);
+        shell_sort_by(&mut input, |a, b| b.cmp(a));
+        assert_eq!(vec![3, 2, 1], input);
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| shell_sort_by(items, f64::total_cmp));
+    }
+}
+
+mod tim_sort {
+    use super::super::{tim_sort, tim_sort_by, tim_sort_by_key};
+    use super::random_data;
+
+    #[test]
+    fn it_sorts_random_input() {
+        for seed in 0..30 {
+            let mut input = random_data(seed, seed as usize * 97, 1000);
+            let mut expected = input.clone();
+            expected.sort();
+            tim_sort(&mut input);
+            assert_eq!(expected, input, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn it_sorts_input_made_of_ascending_and_descending_runs() {
+        let mut input = (0..500)
+            .chain((200..700).rev())
+            .chain(100..300)
+            .chain((0..50).rev())
+            .collect::<Vec<u32>>();
+        let mut expected = input.clone();
+        expected.sort();
+        tim_sort(&mut input);
+        assert_eq!(expected, input);
+    }
+
+    #[test]
+    fn it_is_stable() {
+        let mut input = random_data(5, 1000, 10)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, key)| (key, idx))
+            .collect::<Vec<_>>();
+        input[100..400].reverse();
+        let mut expected = input.clone();
+        expected.sort_by_key(|(key, _)| *key);
+        tim_sort_by_key(&mut input, |(key, _)| *key);
+        assert_eq!(expected, input);
+    }
+
+    #[test]
+    fn it_sorts_floats_with_total_cmp() {
+        super::assert_sorts_floats_with_total_cmp(|items| tim_sort_by(items, f64::total_cmp));
+    }
+}
+
+mod bucket_sort {
+    use super::super::bucket_sort;
+
+    #[test]
+    fn it_sorts_floats() {
+        let mut input = vec![0.42, 0.32, 0.23, 0.52, 0.25, 0.47, 0.51, -3.5, 12.0, 0.25];
+        bucket_sort(&mut input);
+        assert_eq!(
+            vec![-3.5, 0.23, 0.25, 0.25, 0.32, 0.42, 0.47, 0.51, 0.52, 12.0],
+            input
+        );
+
+        let mut input = vec![2.5_f32, 1.5, 0.5];
+        bucket_sort(&mut input);
+        assert_eq!(vec![0.5_f32, 1.5, 2.5], input);
+    }
+
+    #[test]
+    fn it_places_infinities_at_the_ends() {
+        let mut input = vec![1.0, f64::INFINITY, -2.0, f64::NEG_INFINITY, 1.0];
+        bucket_sort(&mut input);
+        assert_eq!(
+            vec![f64::NEG_INFINITY, -2.0, 1.0, 1.0, f64::INFINITY],
+            input
+        );
+    }
+}
+
+mod lazy_enumeration {
+    use super::super::{
+        combinations_iter, pair_indices, pair_indices_iter, pairs, pairs_iter, subarrays,
+        subarrays_iter, windows_iter,
+    };
+
+    fn assert_exact_size<I: Iterator>(mut iter: I) {
+        let mut expected = iter.size_hint().0;
+        assert_eq!((expected, Some(expected)), iter.size_hint());
+        while iter.next().is_some() {
+            expected -= 1;
+            assert_eq!((expected, Some(expected)), iter.size_hint());
+        }
+        assert_eq!(0, expected);
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn iterators_match_the_materialized_versions() {
+        for len in 0..8 {
+            let items = (0..len).collect::<Vec<u32>>();
+            assert_eq!(
+                pairs(&items),
+                pairs_iter(&items)
+                    .map(|(a, b)| (*a, *b))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                pair_indices(&items),
+                pair_indices_iter(len as usize).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                subarrays(&items),
+                subarrays_iter(&items).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                len as usize * (len as usize + 1) / 2,
+                subarrays(&items).len()
+            );
+        }
+    }
+
+    #[test]
+    fn iterators_report_exact_sizes() {
+        for len in 0..8 {
+            let items = (0..len).collect::<Vec<u32>>();
+            assert_exact_size(pairs_iter(&items));
+            assert_exact_size(subarrays_iter(&items));
+            for k in 0..=len as usize + 1 {
+                assert_exact_size(combinations_iter(&items, k));
+                assert_exact_size(windows_iter(&items, k));
+            }
+        }
+    }
+
+    #[test]
+    fn pair_and_subarray_counts_outgrowing_usize_still_iterate() {
+        let mut pairs = pair_indices_iter(usize::MAX);
+        assert_eq!((usize::MAX, None), pairs.size_hint());
+        assert_eq!(Some((0, 1)), pairs.next());
+        assert_eq!((usize::MAX, None), pairs.size_hint());
+
+        let items = [(); usize::MAX];
+        let mut subarrays = subarrays_iter(&items);
+        assert_eq!((usize::MAX, None), subarrays.size_hint());
+        assert_eq!(Some(1), subarrays.next().map(<[()]>::len));
+
+        // 2^32 choose 2 is 2^63 - 2^31, which still fits a 64-bit usize
+        if usize::BITS == 64 {
+            let len = 1_usize << 32;
+            let count = len / 2 * (len - 1);
+            assert_eq!((count, Some(count)), pair_indices_iter(len).size_hint());
+        }
+    }
+
+    #[test]
+    fn combinations_come_in_lexicographic_order() {
+        let items = ['a', 'b', 'c', 'd'];
+        let combinations = combinations_iter(&items, 2)
+            .map(|c| c.into_iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["ab", "ac", "ad", "bc", "bd", "cd"], combinations);
+        assert_eq!(
+            vec![Vec::<&char>::new()],
+            combinations_iter(&items, 0).collect::<Vec<_>>()
+        );
+        assert_eq!(0, combinations_iter(&items, 5).count());
+        assert_eq!(
+            (184_756, Some(184_756)),
+            combinations_iter(&[0; 20], 10).size_hint()
+        );
+    }
+
+    #[test]
+    fn combinations_outgrowing_usize_still_iterate() {
+        // C(200, 100) is about 9 * 10^58, past even u128
+        let items = (0..200).collect::<Vec<u32>>();
+        let mut combinations = combinations_iter(&items, 100);
+        assert_eq!((usize::MAX, None), combinations.size_hint());
+        assert_eq!(
+            Some((0..100).collect::<Vec<_>>()),
+            combinations
+                .next()
+                .map(|c| c.into_iter().copied().collect::<Vec<_>>())
+        );
+        assert_eq!(
+            Some((0..99).chain([100]).collect::<Vec<_>>()),
+            combinations
+                .next()
+                .map(|c| c.into_iter().copied().collect::<Vec<_>>())
+        );
+        assert_eq!(10_000, combinations.by_ref().take(10_000).count());
+        assert_eq!((usize::MAX, None), combinations.size_hint());
+
+        let items = (0..64).collect::<Vec<u32>>();
+        assert_eq!(
+            (1_832_624_140_942_590_534, Some(1_832_624_140_942_590_534)),
+            combinations_iter(&items, 32).size_hint()
+        );
+        let items = (0..70).collect::<Vec<u32>>();
+        assert_eq!(
+            (usize::MAX, None),
+            combinations_iter(&items, 35).size_hint()
+        );
+    }
+
+    #[test]
+    fn windows_slide_from_both_ends() {
+        let items = [1, 2, 3, 4, 5];
+        let mut windows = windows_iter(&items, 3);
+        assert_eq!(Some(&[1, 2, 3][..]), windows.next());
+        assert_eq!(Some(&[3, 4, 5][..]), windows.next_back());
+        assert_eq!(Some(&[2, 3, 4][..]), windows.next());
+        assert_eq!(None, windows.next_back());
+        assert_eq!(0, windows_iter(&items, 0).count());
+        assert_eq!(0, windows_iter(&items, 6).count());
+        assert_eq!(
+            items.windows(2).collect::<Vec<_>>(),
+            windows_iter(&items, 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn callers_can_stop_early_on_huge_inputs() {
+        let items = vec![1_u64; 1 << 20];
+        let count = (1 << 20) * ((1 << 20) - 1) / 2;
+        assert_eq!((count, Some(count)), pairs_iter(&items).size_hint());
+        assert_eq!(
+            Some(&[1_u64; 3][..]),
+            subarrays_iter(&items).find(|subarray| subarray.len() == 3)
+        );
+        assert_eq!(
+            10,
+            combinations_iter(&items, 4)
+                .take(10)
+                .filter(|c| c.len() == 4)
+                .count()
+        );
+    }
+}
+
 mod chopsticks {
     use super::super::chopsticks;
 
@@ -432,17 +1047,21 @@ mod binary_string {
 
 mod print_2d {
     use super::super::print_2d::*;
+    use super::{numbered_grid, GRID_SHAPES};
 
     #[test]
     fn spiral_print_works() {
         assert_eq!(
             vec![1, 2, 3, 4, 8, 12, 16, 15, 14, 13, 9, 5, 6, 7, 11, 10],
-            spiral_print(Vec2d::from(vec![
-                vec![1, 2, 3, 4],     //
-                vec![5, 6, 7, 8],     //
-                vec![9, 10, 11, 12],  //
-                vec![13, 14, 15, 16], //
-            ]))
+            spiral_print(
+                Vec2d::try_from(vec![
+                    vec![1, 2, 3, 4],     //
+                    vec![5, 6, 7, 8],     //
+                    vec![9, 10, 11, 12],  //
+                    vec![13, 14, 15, 16], //
+                ])
+                .unwrap()
+            )
         )
     }
 
@@ -450,15 +1069,143 @@ mod print_2d {
     fn reverse_wave_print_works() {
         assert_eq!(
             vec![4, 8, 12, 16, 15, 11, 7, 3, 2, 6, 10, 14, 13, 9, 5, 1],
-            reverse_wave_print(Vec2d::from(vec![
-                vec![1, 2, 3, 4],     //
-                vec![5, 6, 7, 8],     //
-                vec![9, 10, 11, 12],  //
-                vec![13, 14, 15, 16], //
-            ]))
-        )
+            reverse_wave_print(
+                Vec2d::try_from(vec![
+                    vec![1, 2, 3, 4],     //
+                    vec![5, 6, 7, 8],     //
+                    vec![9, 10, 11, 12],  //
+                    vec![13, 14, 15, 16], //
+                ])
+                .unwrap()
+            )
+        );
+        assert_eq!(
+            vec![3, 6, 5, 2, 1, 4],
+            reverse_wave_print(Vec2d::try_from(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap())
+        );
+        assert_eq!(
+            Vec::<u32>::new(),
+            reverse_wave_print(Vec2d::try_from(Vec::<Vec<u32>>::new()).unwrap())
+        );
     }
-}
+
+    fn values<'a>(traversal: impl Iterator<Item = ((usize, usize), &'a usize)>) -> Vec<usize> {
+        traversal.map(|(_, value)| *value).collect()
+    }
+
+    const ORDERS: [Order; 8] = [
+        Order::Spiral(Rotation::Clockwise, SpiralDirection::Inward),
+        Order::Spiral(Rotation::Clockwise, SpiralDirection::Outward),
+        Order::Spiral(Rotation::CounterClockwise, SpiralDirection::Inward),
+        Order::Spiral(Rotation::CounterClockwise, SpiralDirection::Outward),
+        Order::Wave(Axis::Rows),
+        Order::Wave(Axis::Columns),
+        Order::Diagonal,
+        Order::AntiDiagonal,
+    ];
+
+    #[test]
+    fn traversals_follow_their_order() {
+        let grid = numbered_grid(3, 3);
+        let cw = (Rotation::Clockwise, SpiralDirection::Inward);
+        let ccw = (Rotation::CounterClockwise, SpiralDirection::Inward);
+        let cw_out = (Rotation::Clockwise, SpiralDirection::Outward);
+        let ccw_out = (Rotation::CounterClockwise, SpiralDirection::Outward);
+        assert_eq!(
+            vec![1, 2, 3, 6, 9, 8, 7, 4, 5],
+            values(spiral(&grid, cw.0, cw.1))
+        );
+        assert_eq!(
+            vec![1, 4, 7, 8, 9, 6, 3, 2, 5],
+            values(spiral(&grid, ccw.0, ccw.1))
+        );
+        assert_eq!(
+            vec![5, 2, 3, 6, 9, 8, 7, 4, 1],
+            values(spiral(&grid, cw_out.0, cw_out.1))
+        );
+        assert_eq!(
+            vec![5, 4, 7, 8, 9, 6, 3, 2, 1],
+            values(spiral(&grid, ccw_out.0, ccw_out.1))
+        );
+        assert_eq!(
+            vec![1, 2, 3, 6, 5, 4, 7, 8, 9],
+            values(wave(&grid, Axis::Rows))
+        );
+        assert_eq!(
+            vec![1, 4, 7, 8, 5, 2, 3, 6, 9],
+            values(wave(&grid, Axis::Columns))
+        );
+        assert_eq!(
+            values(wave(&grid, Axis::Rows)),
+            values(boustrophedon(&grid))
+        );
+        assert_eq!(
+            vec![1, 2, 4, 7, 5, 3, 6, 8, 9],
+            values(diagonal_zigzag(&grid))
+        );
+        assert_eq!(
+            vec![3, 2, 6, 9, 5, 1, 4, 8, 7],
+            values(anti_diagonal_zigzag(&grid))
+        );
+    }
+
+    #[test]
+    fn spiral_print_handles_rectangular_grids() {
+        let print = |width, height| spiral_print(numbered_grid(width, height).map(|v| *v as u32));
+        assert_eq!(vec![1, 2, 3, 4, 8, 12, 11, 10, 9, 5, 6, 7], print(4, 3));
+        assert_eq!(vec![1, 2, 4, 6, 8, 7, 5, 3], print(2, 4));
+        assert_eq!(vec![1, 2, 3, 4, 5], print(1, 5));
+        assert_eq!(vec![1, 2, 3, 4, 5], print(5, 1));
+        assert_eq!(Vec::<u32>::new(), print(0, 0));
+    }
+
+    #[test]
+    fn traversals_visit_every_cell_once_and_run_backwards() {
+        for (width, height) in GRID_SHAPES {
+            let grid = numbered_grid(width, height);
+            for order in ORDERS {
+                let traversal = traverse(&grid, order);
+                assert_eq!(width * height, traversal.len());
+                let forward = traversal.clone().collect::<Vec<_>>();
+                for ((col, row), value) in &forward {
+                    assert_eq!(grid[(*col, *row)], **value);
+                }
+                let mut seen = values(forward.iter().copied());
+                seen.sort();
+                assert_eq!((1..=width * height).collect::<Vec<_>>(), seen);
+                let mut backward = traversal.rev().collect::<Vec<_>>();
+                backward.reverse();
+                assert_eq!(forward, backward);
+            }
+        }
+    }
+
+    #[test]
+    fn spirals_and_waves_move_one_step_at_a_time() {
+        let grid = numbered_grid(5, 7);
+        for order in &ORDERS[..6] {
+            let coordinates = traverse(&grid, *order)
+                .map(|(coordinate, _)| coordinate)
+                .collect::<Vec<_>>();
+            for step in coordinates.windows(2) {
+                let ((c1, r1), (c2, r2)) = (step[0], step[1]);
+                assert_eq!(1, c1.abs_diff(c2) + r1.abs_diff(r2), "{order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn traversals_can_start_from_either_end() {
+        let grid = numbered_grid(4, 3);
+        let mut traversal = diagonal_zigzag(&grid);
+        assert_eq!(Some(((0, 0), &1)), traversal.next());
+        assert_eq!(Some(((3, 2), &12)), traversal.next_back());
+        assert_eq!(Some(((0, 1), &5)), traversal.nth(1));
+        assert_eq!(8, traversal.len());
+        assert_eq!(None, traversal.nth(8));
+        assert_eq!(None, traversal.next_back());
+    }
+}
 
 mod search_2d {
     use super::super::print_2d::Vec2d;
@@ -469,60 +1216,65 @@ mod search_2d {
         assert_eq!(
             Some((1, 2)),
             staircase_search(
-                Vec2d::from(vec![
+                Vec2d::try_from(vec![
                     vec![1, 2, 3, 4],     //
                     vec![5, 6, 7, 8],     //
                     vec![9, 10, 11, 12],  //
                     vec![13, 14, 15, 16], //
-                ]),
+                ])
+                .unwrap(),
                 10
             )
         );
         assert_eq!(
             Some((0, 0)),
             staircase_search(
-                Vec2d::from(vec![
+                Vec2d::try_from(vec![
                     vec![1, 2, 3, 4],     //
                     vec![5, 6, 7, 8],     //
                     vec![9, 10, 11, 12],  //
                     vec![13, 14, 15, 16], //
-                ]),
+                ])
+                .unwrap(),
                 1
             )
         );
         assert_eq!(
             Some((3, 3)),
             staircase_search(
-                Vec2d::from(vec![
+                Vec2d::try_from(vec![
                     vec![1, 2, 3, 4],     //
                     vec![5, 6, 7, 8],     //
                     vec![9, 10, 11, 12],  //
                     vec![13, 14, 15, 16], //
-                ]),
+                ])
+                .unwrap(),
                 16
             )
         );
         assert_eq!(
             None,
             staircase_search(
-                Vec2d::from(vec![
+                Vec2d::try_from(vec![
                     vec![1, 2, 3, 4],     //
                     vec![5, 6, 7, 8],     //
                     vec![9, 10, 11, 12],  //
                     vec![13, 14, 15, 16], //
-                ]),
+                ])
+                .unwrap(),
                 0
             )
         );
         assert_eq!(
             None,
             staircase_search(
-                Vec2d::from(vec![
+                Vec2d::try_from(vec![
                     vec![1, 2, 3, 4],     //
                     vec![5, 6, 7, 8],     //
                     vec![9, 10, 11, 12],  //
                     vec![13, 14, 15, 16], //
-                ]),
+                ])
+                .unwrap(),
                 17
             )
         );
@@ -538,14 +1290,17 @@ mod mango {
         fn it_works() {
             assert_eq!(
                 3,
-                best_split(Vec2d::from(vec![
-                    vec![0, 1, 1, 0, 0, 0], //
-                    vec![1, 0, 0, 1, 1, 0], //
-                    vec![0, 1, 0, 0, 0, 0], //
-                    vec![0, 1, 1, 0, 0, 1], //
-                    vec![1, 0, 0, 1, 1, 0], //
-                    vec![0, 1, 0, 0, 0, 0]
-                ]))
+                best_split(
+                    Vec2d::try_from(vec![
+                        vec![0, 1, 1, 0, 0, 0], //
+                        vec![1, 0, 0, 1, 1, 0], //
+                        vec![0, 1, 0, 0, 0, 0], //
+                        vec![0, 1, 1, 0, 0, 1], //
+                        vec![1, 0, 0, 1, 1, 0], //
+                        vec![0, 1, 0, 0, 0, 0]
+                    ])
+                    .unwrap()
+                )
             )
         }
     }
@@ -597,6 +1352,7 @@ mod matrix {
     mod sub_matrix {
         mod sum {
             use super::super::super::super::matrix::*;
+            use super::super::super::random_data;
 
             #[test]
             fn it_works() {
@@ -619,6 +1375,45 @@ mod matrix {
                 assert_eq!(38, matrix.reduced_submatrix_value(2, 2, 4, 3));
                 assert_eq!(38, matrix.reduced_submatrix_value(2, 1, 3, 3));
             }
+
+            #[test]
+            fn every_backend_answers_queries_while_the_matrix_changes() {
+                let (width, height) = (7, 5);
+                let cells = random_data(12, width * height, 50);
+                for backend in [QueryBackend::PrefixTable, QueryBackend::Fenwick] {
+                    let mut expected = cells.clone();
+                    let mut matrix = QueryableMatrix2d::from((
+                        width,
+                        cells.clone(),
+                        || 0,
+                        |u: &mut u64, t: u32| *u += t as u64,
+                        |u1: &mut u64, u2| *u1 += u2,
+                        |u1: &mut u64, u2| *u1 -= u2,
+                    ))
+                    .with_backend(backend);
+                    assert_eq!(backend, matrix.backend());
+                    let ops = random_data(13, 240, 1000);
+                    for op in ops.chunks(6) {
+                        let (col, row) = (op[0] as usize % width, op[1] as usize % height);
+                        matrix.set(col, row, op[2] % 50);
+                        expected[row * width + col] = op[2] % 50;
+                        assert_eq!(op[2] % 50, matrix.get(col, row));
+
+                        let (col1, col2) = (op[3] as usize % width, op[4] as usize % width);
+                        let (row1, row2) = (op[5] as usize % height, op[0] as usize % height);
+                        let (col1, col2) = (col1.min(col2), col1.max(col2));
+                        let (row1, row2) = (row1.min(row2), row1.max(row2));
+                        let naive = (row1..=row2)
+                            .flat_map(|row| (col1..=col2).map(move |col| (col, row)))
+                            .map(|(col, row)| expected[row * width + col] as u64)
+                            .sum::<u64>();
+                        assert_eq!(
+                            naive,
+                            matrix.reduced_submatrix_value(col1, row1, col2, row2)
+                        );
+                    }
+                }
+            }
         }
     }
 }
@@ -863,95 +1658,107 @@ mod fruit {
 }
 
 mod image {
-    use super::super::image::rotate_90_degrees;
-    use super::super::print_2d::Vec2d;
+    use super::super::image::{
+        anti_transpose, crop, flip_horizontal, flip_vertical, pad, rotate, rotate_90_degrees,
+        transpose, Angle, Dihedral,
+    };
+    use super::super::print_2d::{Rotation, Vec2d};
+    use super::{numbered_grid, GRID_SHAPES};
 
     #[test]
     fn it_rotates_an_image_with_no_pixels_correctly() {
-        let mut image = Vec2d::from(Vec::<Vec<u32>>::new());
+        let mut image = Vec2d::try_from(Vec::<Vec<u32>>::new()).unwrap();
         rotate_90_degrees(&mut image);
-        assert_eq!(Vec2d::from(Vec::<Vec<u32>>::new()), image);
+        assert_eq!(Vec2d::try_from(Vec::<Vec<u32>>::new()).unwrap(), image);
     }
 
     #[test]
     fn it_rotates_an_image_with_a_single_pixel_correctly() {
-        let mut image = Vec2d::from(vec![vec![10]]);
+        let mut image = Vec2d::try_from(vec![vec![10]]).unwrap();
         rotate_90_degrees(&mut image);
-        assert_eq!(Vec2d::from(vec![vec![10]]), image);
+        assert_eq!(Vec2d::try_from(vec![vec![10]]).unwrap(), image);
     }
 
     #[test]
     fn it_rotates_a_3_by_3_pixel_image_correctly() {
-        let mut image = Vec2d::from(vec![
+        let mut image = Vec2d::try_from(vec![
             vec![1, 2, 3], //
             vec![4, 5, 6], //
             vec![7, 8, 9], //
-        ]);
+        ])
+        .unwrap();
         rotate_90_degrees(&mut image);
         assert_eq!(
-            Vec2d::from(vec![
+            Vec2d::try_from(vec![
                 vec![7, 4, 1], //
                 vec![8, 5, 2], //
                 vec![9, 6, 3], //
-            ]),
+            ])
+            .unwrap(),
             image
         );
     }
 
     #[test]
     fn it_rotates_a_5_by_5_pixel_image_correctly() {
-        let mut image = Vec2d::from(vec![
+        let mut image = Vec2d::try_from(vec![
             vec![1, 2, 3, 4, 5],      //
             vec![6, 7, 8, 9, 10],     //
             vec![11, 12, 13, 14, 15], //
             vec![16, 17, 18, 19, 20], //
             vec![21, 22, 23, 24, 25], //
-        ]);
+        ])
+        .unwrap();
         rotate_90_degrees(&mut image);
         assert_eq!(
-            Vec2d::from(vec![
+            Vec2d::try_from(vec![
                 vec![21, 16, 11, 6, 1],  //
                 vec![22, 17, 12, 7, 2],  //
                 vec![23, 18, 13, 8, 3],  //
                 vec![24, 19, 14, 9, 4],  //
                 vec![25, 20, 15, 10, 5], //
-            ]),
+            ])
+            .unwrap(),
             image
         );
     }
 
     #[test]
     fn it_rotates_a_2_by_2_pixel_image_correctly() {
-        let mut image = Vec2d::from(vec![
+        let mut image = Vec2d::try_from(vec![
             vec![1, 2], //
             vec![3, 4], //
-        ]);
+        ])
+        .unwrap();
         rotate_90_degrees(&mut image);
         assert_eq!(
-            Vec2d::from(vec![
+            Vec2d::try_from(vec![
                 vec![3, 1], //
                 vec![4, 2], //
-            ]),
+            ])
+            .unwrap(),
             image
         );
     }
 
     #[test]
     fn it_rotates_a_4_by_4_pixel_image_correctly() {
-        let mut image = Vec2d::from(vec![
+        let mut image = Vec2d::try_from(vec![
             vec![1, 2, 3, 4],     //
             vec![5, 6, 7, 8],     //
             vec![9, 10, 11, 12],  //
             vec![13, 14, 15, 16], //
-        ]);
+        ])
+        .unwrap();
         rotate_90_degrees(&mut image);
         assert_eq!(
-            Vec2d::from(vec![
+            Vec2d::try_from(vec![
                 vec![13, 9, 5, 1],  //
                 vec![14, 10, 6, 2], //
                 vec![15, 11, 7, 3], //
                 vec![16, 12, 8, 4], //
-            ]),
+            ])
+            .unwrap(),
             image
         );
     }
@@ -959,16 +1766,421 @@ mod image {
     #[test]
     #[should_panic(expected = "image must be square")]
     fn it_panics_on_an_image_where_the_width_does_not_equal_the_height() {
-        let mut image = Vec2d::from(vec![
+        let mut image = Vec2d::try_from(vec![
             vec![1, 2, 3, 4],     //
             vec![5, 6, 7, 8],     //
             vec![9, 10, 11, 12],  //
             vec![13, 14, 15, 16], //
             vec![17, 18, 19, 20], //
-        ]);
+        ])
+        .unwrap();
         // this will panic
         rotate_90_degrees(&mut image);
     }
+
+    // builds the expected image pixel by pixel from where each one comes from in the source
+    fn remapped(source: &Vec2d<usize>, transform: Dihedral) -> Vec2d<usize> {
+        let (w, h) = (source.width(), source.height());
+        let (width, height) = match transform {
+            Dihedral::Rotate90
+            | Dihedral::Rotate270
+            | Dihedral::Transpose
+            | Dihedral::AntiTranspose => (h, w),
+            _ => (w, h),
+        };
+        let mut pixels = Vec::new();
+        for r in 0..height {
+            for c in 0..width {
+                let (col, row) = match transform {
+                    Dihedral::Identity => (c, r),
+                    Dihedral::Rotate90 => (r, h - 1 - c),
+                    Dihedral::Rotate180 => (w - 1 - c, h - 1 - r),
+                    Dihedral::Rotate270 => (w - 1 - r, c),
+                    Dihedral::FlipHorizontal => (w - 1 - c, r),
+                    Dihedral::FlipVertical => (c, h - 1 - r),
+                    Dihedral::Transpose => (r, c),
+                    Dihedral::AntiTranspose => (w - 1 - r, h - 1 - c),
+                };
+                pixels.push(*source.get(col, row).unwrap());
+            }
+        }
+        Vec2d::try_from((width, pixels)).unwrap()
+    }
+
+    #[test]
+    fn it_rotates_a_rectangular_image_in_both_directions() {
+        let mut image = Vec2d::try_from(vec![
+            vec![1, 2, 3], //
+            vec![4, 5, 6], //
+        ])
+        .unwrap();
+        rotate(&mut image, Angle::Deg90, Rotation::Clockwise);
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![4, 1], //
+                vec![5, 2], //
+                vec![6, 3], //
+            ])
+            .unwrap(),
+            image
+        );
+        rotate(&mut image, Angle::Deg180, Rotation::CounterClockwise);
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![3, 6], //
+                vec![2, 5], //
+                vec![1, 4], //
+            ])
+            .unwrap(),
+            image
+        );
+        rotate(&mut image, Angle::Deg90, Rotation::CounterClockwise);
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![6, 5, 4], //
+                vec![3, 2, 1], //
+            ])
+            .unwrap(),
+            image
+        );
+        rotate(&mut image, Angle::Deg270, Rotation::Clockwise);
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![4, 1], //
+                vec![5, 2], //
+                vec![6, 3], //
+            ])
+            .unwrap(),
+            image
+        );
+    }
+
+    #[test]
+    fn it_flips_and_transposes_a_rectangular_image() {
+        let image = Vec2d::try_from(vec![
+            vec![1, 2, 3], //
+            vec![4, 5, 6], //
+        ])
+        .unwrap();
+        let mut flipped = image.clone();
+        flip_horizontal(&mut flipped);
+        assert_eq!(
+            Vec2d::try_from(vec![vec![3, 2, 1], vec![6, 5, 4]]).unwrap(),
+            flipped
+        );
+        let mut flipped = image.clone();
+        flip_vertical(&mut flipped);
+        assert_eq!(
+            Vec2d::try_from(vec![vec![4, 5, 6], vec![1, 2, 3]]).unwrap(),
+            flipped
+        );
+        let mut transposed = image.clone();
+        transpose(&mut transposed);
+        assert_eq!(
+            Vec2d::try_from(vec![vec![1, 4], vec![2, 5], vec![3, 6]]).unwrap(),
+            transposed
+        );
+        let mut transposed = image;
+        anti_transpose(&mut transposed);
+        assert_eq!(
+            Vec2d::try_from(vec![vec![6, 3], vec![5, 2], vec![4, 1]]).unwrap(),
+            transposed
+        );
+    }
+
+    #[test]
+    fn it_applies_every_dihedral_transform_to_images_of_any_shape() {
+        for (width, height) in GRID_SHAPES {
+            let image = numbered_grid(width, height);
+            for transform in Dihedral::ALL {
+                let mut transformed = image.clone();
+                transform.apply(&mut transformed);
+                assert_eq!(
+                    remapped(&image, transform),
+                    transformed,
+                    "{transform:?} on {width}x{height}"
+                );
+                transform.inverse().apply(&mut transformed);
+                assert_eq!(image, transformed, "inverse of {transform:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn it_composes_dihedral_transforms_like_applying_them_in_turn() {
+        let image = numbered_grid(3, 5);
+        let results = Dihedral::ALL.map(|transform| {
+            let mut transformed = image.clone();
+            transform.apply(&mut transformed);
+            transformed
+        });
+        for (idx, result) in results.iter().enumerate() {
+            assert!(results[idx + 1..].iter().all(|other| other != result));
+        }
+        for first in Dihedral::ALL {
+            for second in Dihedral::ALL {
+                let mut in_turn = image.clone();
+                first.apply(&mut in_turn);
+                second.apply(&mut in_turn);
+                let mut composed = image.clone();
+                first.then(second).apply(&mut composed);
+                assert_eq!(in_turn, composed, "{first:?} then {second:?}");
+            }
+            assert_eq!(Dihedral::Identity, first.then(first.inverse()));
+        }
+    }
+
+    #[test]
+    fn it_crops_an_image() {
+        let image = numbered_grid(4, 3);
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![6, 7],   //
+                vec![10, 11], //
+            ])
+            .unwrap(),
+            crop(&image, 1..3, 1..3)
+        );
+        assert_eq!(0, crop(&image, 2..2, 0..3).size());
+    }
+
+    #[test]
+    fn it_pads_an_image() {
+        let image = Vec2d::try_from(vec![
+            vec![1, 2], //
+            vec![3, 4], //
+        ])
+        .unwrap();
+        assert_eq!(
+            Vec2d::try_from(vec![
+                vec![0, 0, 0, 0, 0], //
+                vec![0, 1, 2, 0, 0], //
+                vec![0, 3, 4, 0, 0], //
+                vec![0, 0, 0, 0, 0], //
+                vec![0, 0, 0, 0, 0], //
+            ])
+            .unwrap(),
+            pad(&image, (1, 1, 2, 2), 0)
+        );
+        assert_eq!(
+            Vec2d::new(3, 2, 7),
+            pad(
+                &Vec2d::try_from(Vec::<Vec<u32>>::new()).unwrap(),
+                (1, 0, 2, 2),
+                7
+            )
+        );
+    }
+
+    mod netpbm {
+        use super::super::super::image::netpbm::{
+            read_pbm, read_pgm, read_ppm, write_pbm, write_pgm, write_ppm, Encoding, Format,
+            Graymap, NetpbmErrorKind, Pixmap,
+        };
+        use super::super::super::image::{rotate, rotate_90_degrees, Angle};
+        use super::super::super::print_2d::{Rotation, Vec2d};
+
+        fn banner() -> Vec2d<u8> {
+            Vec2d::try_from(vec![
+                vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1], //
+                vec![0, 1, 1, 0, 1, 1, 0, 1, 1, 0], //
+                vec![1, 1, 1, 1, 1, 0, 0, 0, 0, 0], //
+            ])
+            .unwrap()
+        }
+
+        #[test]
+        fn it_rotates_a_graymap_fixture_end_to_end() {
+            let square = read_pgm::<u8, _>(&include_bytes!("../fixtures/square.pgm")[..]).unwrap();
+            assert_eq!(9, square.maxval);
+            let mut image = square.image.map(|&pixel| pixel as u32);
+            rotate_90_degrees(&mut image);
+            let rotated = Graymap {
+                image: image.map(|&pixel| pixel as u8),
+                maxval: square.maxval,
+            };
+            let mut written = Vec::new();
+            write_pgm(&mut written, &rotated, Encoding::Plain).unwrap();
+            assert_eq!(
+                &include_bytes!("../fixtures/square_rotated.pgm")[..],
+                &written[..]
+            );
+        }
+
+        #[test]
+        fn it_rotates_a_rectangular_binary_graymap_fixture_end_to_end() {
+            let mut strip =
+                read_pgm::<u8, _>(&include_bytes!("../fixtures/strip.pgm")[..]).unwrap();
+            rotate(&mut strip.image, Angle::Deg90, Rotation::CounterClockwise);
+            let mut written = Vec::new();
+            write_pgm(&mut written, &strip, Encoding::Binary).unwrap();
+            assert_eq!(
+                &include_bytes!("../fixtures/strip_rotated_ccw.pgm")[..],
+                &written[..]
+            );
+        }
+
+        #[test]
+        fn it_reads_plain_and_binary_bitmaps() {
+            let plain = read_pbm(&include_bytes!("../fixtures/banner.pbm")[..]).unwrap();
+            let binary = read_pbm(&include_bytes!("../fixtures/banner_binary.pbm")[..]).unwrap();
+            assert_eq!(banner(), plain);
+            assert_eq!(banner(), binary);
+        }
+
+        #[test]
+        fn it_writes_bitmaps_that_read_back_the_same() {
+            let mut written = Vec::new();
+            write_pbm(&mut written, &banner(), Encoding::Binary).unwrap();
+            assert_eq!(
+                &include_bytes!("../fixtures/banner_binary.pbm")[..],
+                &written[..]
+            );
+            let mut written = Vec::new();
+            write_pbm(&mut written, &banner(), Encoding::Plain).unwrap();
+            assert_eq!(
+                "P1\n10 3\n1 0 0 0 0 0 0 0 0 1\n0 1 1 0 1 1 0 1 1 0\n1 1 1 1 1 0 0 0 0 0\n",
+                String::from_utf8(written).unwrap()
+            );
+        }
+
+        #[test]
+        fn it_reads_and_writes_sixteen_bit_graymaps() {
+            let fixture = &include_bytes!("../fixtures/depth.pgm")[..];
+            let depth = read_pgm::<u16, _>(fixture).unwrap();
+            assert_eq!(1000, depth.maxval);
+            assert_eq!(
+                Vec2d::try_from(vec![vec![0, 500, 1000]]).unwrap(),
+                depth.image
+            );
+            for encoding in [Encoding::Plain, Encoding::Binary] {
+                let mut written = Vec::new();
+                write_pgm(&mut written, &depth, encoding).unwrap();
+                assert_eq!(depth, read_pgm::<u16, _>(&written[..]).unwrap());
+            }
+            assert!(matches!(
+                read_pgm::<u8, _>(fixture).unwrap_err().kind(),
+                NetpbmErrorKind::UnsupportedMaxval(1000)
+            ));
+        }
+
+        #[test]
+        fn it_reads_plain_and_binary_pixmaps_as_packed_rgb() {
+            let expected = Pixmap {
+                image: Vec2d::try_from(vec![
+                    vec![0xff0000, 0x00ff00], //
+                    vec![0x0000ff, 0x123456], //
+                ])
+                .unwrap(),
+                maxval: 255,
+            };
+            let plain = read_ppm(&include_bytes!("../fixtures/colors.ppm")[..]).unwrap();
+            let binary = read_ppm(&include_bytes!("../fixtures/colors_binary.ppm")[..]).unwrap();
+            assert_eq!(expected, plain);
+            assert_eq!(expected, binary);
+            let mut written = Vec::new();
+            write_ppm(&mut written, &expected, Encoding::Binary).unwrap();
+            assert_eq!(
+                &include_bytes!("../fixtures/colors_binary.ppm")[..],
+                &written[..]
+            );
+        }
+
+        #[test]
+        fn it_rejects_sixteen_bit_pixmaps() {
+            let err = read_ppm(&include_bytes!("../fixtures/deep_colors.ppm")[..]).unwrap_err();
+            assert!(matches!(
+                err.kind(),
+                NetpbmErrorKind::UnsupportedMaxval(65535)
+            ));
+        }
+
+        #[test]
+        fn it_wraps_long_plain_rows() {
+            let pixmap = Pixmap {
+                image: Vec2d::new(7, 1, 0x646464),
+                maxval: 200,
+            };
+            let mut written = Vec::new();
+            write_ppm(&mut written, &pixmap, Encoding::Plain).unwrap();
+            let text = String::from_utf8(written).unwrap();
+            assert!(text.lines().all(|line| line.len() <= 70));
+            assert_eq!(pixmap, read_ppm(text.as_bytes()).unwrap());
+        }
+
+        #[test]
+        fn it_rejects_malformed_images() {
+            let read = |bytes: &[u8]| read_pgm::<u8, _>(bytes).unwrap_err();
+            assert!(matches!(
+                read(b"P7\n1 1\n1\n0\n").kind(),
+                NetpbmErrorKind::BadMagicNumber
+            ));
+            assert!(matches!(
+                read(b"P3\n1 1\n1\n0 0 0\n").kind(),
+                NetpbmErrorKind::WrongFormat {
+                    expected: Format::Graymap,
+                    found: Format::Pixmap
+                }
+            ));
+            assert!(matches!(
+                read(b"P2\nx 1\n1\n0\n").kind(),
+                NetpbmErrorKind::InvalidHeader
+            ));
+            assert!(matches!(
+                read(b"P2\n1 1\n0\n0\n").kind(),
+                NetpbmErrorKind::UnsupportedMaxval(0)
+            ));
+            assert!(matches!(
+                read(b"P2\n2 1\n9\n3").kind(),
+                NetpbmErrorKind::UnexpectedEof
+            ));
+            assert!(matches!(
+                read(b"P5\n2 1\n9\n\x03").kind(),
+                NetpbmErrorKind::UnexpectedEof
+            ));
+            assert!(matches!(
+                read(b"P2\n1 1\n9\n10\n").kind(),
+                NetpbmErrorKind::SampleOutOfRange {
+                    sample: 10,
+                    maxval: 9
+                }
+            ));
+            assert!(matches!(
+                read(b"P2\n1 1\n9\n-1\n").kind(),
+                NetpbmErrorKind::InvalidData
+            ));
+            assert!(matches!(
+                read_pbm(&b"P1\n2 1\n0 2\n"[..]).unwrap_err().kind(),
+                NetpbmErrorKind::InvalidData
+            ));
+            // a bitmap claiming rows of half a gigabyte, with a few bytes of raster behind it
+            assert!(matches!(
+                read_pbm(&b"P4\n4294967295 2\n\xff\xff"[..])
+                    .unwrap_err()
+                    .kind(),
+                NetpbmErrorKind::UnexpectedEof
+            ));
+        }
+
+        #[test]
+        fn it_refuses_to_write_samples_above_the_maximum() {
+            let graymap = Graymap {
+                image: Vec2d::try_from(vec![vec![3u8, 10]]).unwrap(),
+                maxval: 9,
+            };
+            for encoding in [Encoding::Plain, Encoding::Binary] {
+                assert!(matches!(
+                    write_pgm(Vec::new(), &graymap, encoding)
+                        .unwrap_err()
+                        .kind(),
+                    NetpbmErrorKind::SampleOutOfRange {
+                        sample: 10,
+                        maxval: 9
+                    }
+                ));
+            }
+        }
+    }
 }
 
 mod zeroes {
@@ -977,20 +2189,22 @@ mod zeroes {
 
     #[test]
     fn it_works() {
-        let input = Vec2d::<u32>::from(vec![
+        let input = Vec2d::<u32>::try_from(vec![
             vec![1, 2, 3, 4, 5],      //
             vec![6, 0, 8, 9, 10],     //
             vec![11, 12, 13, 14, 15], //
             vec![16, 17, 0, 19, 20],  //
             vec![21, 22, 23, 24, 0],  //
-        ]);
-        let expected = Vec2d::<u32>::from(vec![
+        ])
+        .unwrap();
+        let expected = Vec2d::<u32>::try_from(vec![
             vec![1, 0, 0, 4, 0],   //
             vec![0, 0, 0, 0, 0],   //
             vec![11, 0, 0, 14, 0], //
             vec![0, 0, 0, 0, 0],   //
             vec![0, 0, 0, 0, 0],   //
-        ]);
+        ])
+        .unwrap();
         let actual = make_zeroes(input);
         assert_eq!(expected, actual);
     }
@@ -1790,7 +3004,19 @@ mod recursion {
         mod sort {
 
             mod merge_sort {
-                use super::super::super::super::super::divide_conquer::sort::merge_sort;
+                use std::{panic, rc::Rc};
+
+                use super::super::super::super::super::divide_conquer::sort::{
+                    merge_sort, merge_sort_by, merge_sort_by_key, merge_sort_by_with,
+                    merge_sort_with, MergeStrategy,
+                };
+                use super::super::super::super::random_data;
+
+                const STRATEGIES: [MergeStrategy; 3] = [
+                    MergeStrategy::TopDown,
+                    MergeStrategy::BottomUp,
+                    MergeStrategy::InPlace,
+                ];
 
                 #[test]
                 fn it_works_with_empty_array() {
@@ -1829,10 +3055,126 @@ mod recursion {
                         data
                     );
                 }
+
+                #[test]
+                fn it_sorts_descending_with_a_comparator() {
+                    let mut data = [7, 12, 9, 6, 1, 13, 7, 10, 31, 2, 5, 3, 99, 37, 26, 14];
+                    merge_sort_by(&mut data, |a, b| b.cmp(a));
+                    assert_eq!(
+                        [99, 37, 31, 26, 14, 13, 12, 10, 9, 7, 7, 6, 5, 3, 2, 1],
+                        data
+                    );
+                }
+
+                #[test]
+                fn it_sorts_floats_with_total_cmp() {
+                    super::super::super::super::assert_sorts_floats_with_total_cmp(|items| {
+                        merge_sort_by(items, f64::total_cmp)
+                    });
+                }
+
+                #[test]
+                fn it_sorts_records_by_a_field() {
+                    let mut data = [(3, 'c'), (1, 'a'), (4, 'd'), (2, 'b')];
+                    merge_sort_by_key(&mut data, |(_, name)| *name);
+                    assert_eq!([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')], data);
+                }
+
+                #[test]
+                fn every_strategy_sorts_random_input() {
+                    for strategy in STRATEGIES {
+                        for seed in 0..50 {
+                            let mut data = random_data(seed, seed as usize * 7, 30);
+                            let mut expected = data.clone();
+                            expected.sort();
+                            merge_sort_with(&mut data, strategy);
+                            assert_eq!(expected, data, "{strategy:?} with seed {seed}");
+                        }
+                    }
+                }
+
+                #[test]
+                fn every_strategy_is_stable() {
+                    for strategy in STRATEGIES {
+                        let mut data = random_data(7, 300, 9)
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, key)| (key, idx))
+                            .collect::<Vec<_>>();
+                        let mut expected = data.clone();
+                        expected.sort();
+                        merge_sort_by_with(&mut data, strategy, |a, b| a.0.cmp(&b.0));
+                        assert_eq!(expected, data, "{strategy:?}");
+                    }
+                }
+
+                #[test]
+                fn every_strategy_sorts_non_copy_items() {
+                    for strategy in STRATEGIES {
+                        let mut data = ["kiwi", "fig", "apple", "date", "banana", "cherry", "egg"]
+                            .map(String::from)
+                            .to_vec();
+                        merge_sort_with(&mut data, strategy);
+                        assert_eq!(
+                            ["apple", "banana", "cherry", "date", "egg", "fig", "kiwi"]
+                                .map(String::from)
+                                .to_vec(),
+                            data
+                        );
+                    }
+                }
+
+                #[test]
+                fn it_neither_loses_nor_duplicates_items_when_the_comparator_panics() {
+                    for strategy in STRATEGIES {
+                        let items = random_data(3, 64, 1000)
+                            .into_iter()
+                            .map(Rc::new)
+                            .collect::<Vec<_>>();
+                        let mut data = items.clone();
+                        let mut comparisons = 0;
+                        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                            merge_sort_by_with(&mut data, strategy, |a, b| {
+                                comparisons += 1;
+                                assert!(comparisons < 150, "comparator gave up");
+                                a.cmp(b)
+                            })
+                        }));
+                        assert!(result.is_err());
+                        assert!(items.iter().all(|item| Rc::strong_count(item) == 2));
+                        drop(data);
+                        assert!(items.iter().all(|item| Rc::strong_count(item) == 1));
+                    }
+                }
             }
 
             mod quick_sort {
-                use super::super::super::super::super::divide_conquer::sort::quick_sort;
+                use super::super::super::super::super::divide_conquer::sort::{
+                    quick_sort, quick_sort_by, quick_sort_by_key, quick_sort_by_with,
+                    quick_sort_with, PivotStrategy, QuickSortConfig,
+                };
+                use super::super::super::super::random_data;
+
+                fn configs() -> Vec<QuickSortConfig> {
+                    let mut configs = Vec::new();
+                    for pivot in [
+                        PivotStrategy::Last,
+                        PivotStrategy::MedianOfThree,
+                        PivotStrategy::Ninther,
+                        PivotStrategy::Random { seed: 42 },
+                    ] {
+                        for three_way in [false, true] {
+                            for insertion_cutoff in [0, 16] {
+                                configs.push(QuickSortConfig {
+                                    pivot,
+                                    three_way,
+                                    insertion_cutoff,
+                                });
+                            }
+                        }
+                    }
+                    configs
+                }
 
                 #[test]
                 fn it_works_with_empty_array() {
@@ -1871,6 +3213,207 @@ mod recursion {
                         data
                     );
                 }
+
+                #[test]
+                fn it_sorts_descending_with_a_comparator() {
+                    let mut data = [7, 12, 9, 6, 1, 13, 7, 10, 31, 2, 5, 3, 99, 37, 26, 14];
+                    quick_sort_by(&mut data, |a, b| b.cmp(a));
+                    assert_eq!(
+                        [99, 37, 31, 26, 14, 13, 12, 10, 9, 7, 7, 6, 5, 3, 2, 1],
+                        data
+                    );
+                }
+
+                #[test]
+                fn it_sorts_floats_with_total_cmp() {
+                    super::super::super::super::assert_sorts_floats_with_total_cmp(|items| {
+                        quick_sort_by(items, f64::total_cmp)
+                    });
+                }
+
+                #[test]
+                fn it_sorts_records_by_a_field() {
+                    let mut data = [(3, 'c'), (1, 'a'), (4, 'd'), (2, 'b')];
+                    quick_sort_by_key(&mut data, |(_, name)| *name);
+                    assert_eq!([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')], data);
+                }
+
+                #[test]
+                fn every_configuration_sorts_random_sorted_reversed_and_uniform_input() {
+                    for config in configs() {
+                        for seed in 0..20 {
+                            let random = random_data(seed, seed as usize * 13, 50);
+                            let mut sorted = random.clone();
+                            sorted.sort();
+                            let reversed = sorted.iter().rev().copied().collect::<Vec<_>>();
+                            let uniform = vec![7_u32; seed as usize * 5];
+                            for input in [&random, &sorted, &reversed, &uniform] {
+                                let mut data = input.clone();
+                                let mut expected = input.clone();
+                                expected.sort();
+                                quick_sort_with(&mut data, config);
+                                assert_eq!(expected, data, "{config:?} with seed {seed}");
+                            }
+                        }
+                    }
+                }
+
+                #[test]
+                fn it_stays_n_log_n_on_sorted_input_with_a_naive_pivot() {
+                    let len = 20_000_u64;
+                    let mut data = (0..len).collect::<Vec<_>>();
+                    let mut comparisons = 0_u64;
+                    quick_sort_by_with(
+                        &mut data,
+                        QuickSortConfig {
+                            pivot: PivotStrategy::Last,
+                            three_way: false,
+                            insertion_cutoff: 0,
+                        },
+                        |a, b| {
+                            comparisons += 1;
+                            a.cmp(b)
+                        },
+                    );
+                    assert_eq!((0..len).collect::<Vec<_>>(), data);
+                    assert!(comparisons < 20 * len * len.ilog2() as u64);
+                }
+
+                #[test]
+                fn random_pivots_are_reproducible_from_the_seed() {
+                    let count_comparisons = |seed| {
+                        let mut data = random_data(1, 500, 1000);
+                        let mut comparisons = 0;
+                        quick_sort_by_with(
+                            &mut data,
+                            QuickSortConfig {
+                                pivot: PivotStrategy::Random { seed },
+                                ..QuickSortConfig::default()
+                            },
+                            |a, b| {
+                                comparisons += 1;
+                                a.cmp(b)
+                            },
+                        );
+                        comparisons
+                    };
+                    assert_eq!(count_comparisons(99), count_comparisons(99));
+                }
+            }
+
+            mod parallel {
+                use super::super::super::super::super::divide_conquer::sort::{
+                    par_merge_sort, par_merge_sort_by_with, par_merge_sort_with, par_quick_sort,
+                    par_quick_sort_by_with, par_quick_sort_with, ParallelConfig, PivotStrategy,
+                    QuickSortConfig,
+                };
+                use super::super::super::super::random_data;
+
+                const CONFIGS: [ParallelConfig; 4] = [
+                    ParallelConfig {
+                        threads: 1,
+                        sequential_cutoff: 16,
+                    },
+                    ParallelConfig {
+                        threads: 2,
+                        sequential_cutoff: 16,
+                    },
+                    ParallelConfig {
+                        threads: 7,
+                        sequential_cutoff: 1,
+                    },
+                    ParallelConfig {
+                        threads: 8,
+                        sequential_cutoff: 0,
+                    },
+                ];
+
+                #[test]
+                fn par_merge_sort_matches_sequential_sort() {
+                    for config in CONFIGS {
+                        for (seed, len) in [(1, 0), (2, 1), (3, 2), (4, 37), (5, 1000)] {
+                            let mut input = random_data(seed, len, 100);
+                            let mut expected = input.clone();
+                            expected.sort();
+                            par_merge_sort_with(&mut input, config);
+                            assert_eq!(expected, input);
+                        }
+                    }
+                }
+
+                #[test]
+                fn par_merge_sort_is_stable() {
+                    for config in CONFIGS {
+                        let mut input = random_data(9, 500, 10)
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, key)| (key, idx))
+                            .collect::<Vec<_>>();
+                        let mut expected = input.clone();
+                        expected.sort_by_key(|&(key, _)| key);
+                        par_merge_sort_by_with(&mut input, config, |a, b| a.0.cmp(&b.0));
+                        assert_eq!(expected, input);
+                    }
+                }
+
+                #[test]
+                fn par_quick_sort_matches_sequential_sort() {
+                    for pivot in [
+                        PivotStrategy::Last,
+                        PivotStrategy::MedianOfThree,
+                        PivotStrategy::Ninther,
+                        PivotStrategy::Random { seed: 11 },
+                    ] {
+                        for three_way in [false, true] {
+                            let quick = QuickSortConfig {
+                                pivot,
+                                three_way,
+                                insertion_cutoff: 8,
+                            };
+                            for config in CONFIGS {
+                                for (seed, len) in [(1, 0), (2, 1), (3, 2), (4, 37), (5, 1000)] {
+                                    let mut input = random_data(seed, len, 50);
+                                    let mut expected = input.clone();
+                                    expected.sort();
+                                    par_quick_sort_by_with(&mut input, quick, config, u32::cmp);
+                                    assert_eq!(expected, input);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                #[test]
+                fn par_sorts_handle_presorted_and_descending_input() {
+                    let ascending = (0..5000).collect::<Vec<u32>>();
+                    let descending = ascending.iter().rev().copied().collect::<Vec<_>>();
+                    for input in [ascending.clone(), descending] {
+                        let mut merged = input.clone();
+                        par_merge_sort(&mut merged);
+                        assert_eq!(ascending, merged);
+                        let mut quick = input.clone();
+                        par_quick_sort(&mut quick);
+                        assert_eq!(ascending, quick);
+                        let mut quick = input;
+                        par_quick_sort_with(&mut quick, CONFIGS[2]);
+                        assert_eq!(ascending, quick);
+                    }
+                }
+
+                #[test]
+                fn par_sorts_support_non_copy_items() {
+                    let mut input = random_data(21, 300, 1000)
+                        .into_iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>();
+                    let mut expected = input.clone();
+                    expected.sort();
+                    let mut quick = input.clone();
+                    par_merge_sort_with(&mut input, CONFIGS[3]);
+                    par_quick_sort_with(&mut quick, CONFIGS[3]);
+                    assert_eq!(expected, input);
+                    assert_eq!(expected, quick);
+                }
             }
 
             mod merge_sort_2d {
@@ -1879,17 +3422,19 @@ mod recursion {
 
                 #[test]
                 fn it_works_on_a_3_by_3_array() {
-                    let expected = Vec2d::from(vec![
+                    let expected = Vec2d::try_from(vec![
                         vec![1, 4, 11],   //
                         vec![9, 15, 18],  //
                         vec![13, 20, 23], //
-                    ]);
+                    ])
+                    .unwrap();
 
-                    let mut data = Vec2d::from(vec![
+                    let mut data = Vec2d::try_from(vec![
                         vec![18, 9, 11],  //
                         vec![1, 4, 15],   //
                         vec![13, 23, 20], //
-                    ]);
+                    ])
+                    .unwrap();
                     merge_sort_2d(&mut data);
 
                     assert_eq! {
@@ -1900,19 +3445,21 @@ mod recursion {
 
                 #[test]
                 fn it_works_on_a_4_by_4_array() {
-                    let expected = Vec2d::from(vec![
+                    let expected = Vec2d::try_from(vec![
                         vec![1, 8, 16, 18],   //
                         vec![4, 13, 19, 23],  //
                         vec![11, 15, 20, 28], //
                         vec![24, 25, 26, 30], //
-                    ]);
+                    ])
+                    .unwrap();
 
-                    let mut data = Vec2d::from(vec![
+                    let mut data = Vec2d::try_from(vec![
                         vec![18, 4, 16, 8],   //
                         vec![23, 13, 20, 11], //
                         vec![28, 24, 26, 25], //
                         vec![1, 30, 15, 19],  //
-                    ]);
+                    ])
+                    .unwrap();
                     merge_sort_2d(&mut data);
 
                     assert_eq! {
@@ -2323,7 +3870,7 @@ mod backtracking {
 
         #[test]
         fn it_works() {
-            let expected_solution = Vec2d::from(vec![
+            let expected_solution = Vec2d::try_from(vec![
                 vec![5, 3, 4, 6, 7, 8, 9, 1, 2], //
                 vec![6, 7, 2, 1, 9, 5, 3, 4, 8], //
                 vec![1, 9, 8, 3, 4, 2, 5, 6, 7], //
@@ -2333,9 +3880,10 @@ mod backtracking {
                 vec![9, 6, 1, 5, 3, 7, 2, 8, 4], //
                 vec![2, 8, 7, 4, 1, 9, 6, 3, 5], //
                 vec![3, 4, 5, 2, 8, 6, 1, 7, 9], //
-            ]);
+            ])
+            .unwrap();
 
-            let mut board = Vec2d::from(vec![
+            let mut board = Vec2d::try_from(vec![
                 vec![5, 3, 0, 0, 7, 0, 0, 0, 0], //
                 vec![6, 0, 0, 1, 9, 5, 0, 0, 0], //
                 vec![0, 9, 8, 0, 0, 0, 0, 6, 0], //
@@ -2345,7 +3893,8 @@ mod backtracking {
                 vec![0, 6, 0, 0, 0, 0, 2, 8, 0], //
                 vec![0, 0, 0, 4, 1, 9, 0, 0, 5], //
                 vec![0, 0, 0, 0, 8, 0, 0, 7, 9], //
-            ]);
+            ])
+            .unwrap();
             assert!(solve_sudoku(&mut board), "board has no solution");
 
             assert_eq!(
@@ -2372,13 +3921,14 @@ mod backtracking {
                 (3, 4), //
             ];
 
-            let maze = Vec2d::from(vec![
+            let maze = Vec2d::try_from(vec![
                 vec![false, true, false, false], //
                 vec![false, false, false, true], //
                 vec![true, false, true, false],  //
                 vec![true, false, false, true],  //
                 vec![true, true, false, false],  //
-            ]);
+            ])
+            .unwrap();
 
             assert_eq!(
                 Some(expected),
@@ -2433,11 +3983,12 @@ mod backtracking {
         #[test]
         fn it_works_with_a_simple_example() {
             assert!(find_word(
-                &Vec2d::from(vec![
+                &Vec2d::try_from(vec![
                     vec!['A', 'B', 'C', 'E'],
                     vec!['S', 'F', 'C', 'S'],
                     vec!['A', 'D', 'E', 'E'],
-                ]),
+                ])
+                .unwrap(),
                 "ABCCED"
             ))
         }
@@ -2445,7 +3996,7 @@ mod backtracking {
         #[test]
         fn it_works_with_a_more_complicated_example() {
             assert!(find_word(
-                &Vec2d::from(vec![
+                &Vec2d::try_from(vec![
                     vec!['A', 'B', 'C', 'E', 'G', 'L'],
                     vec!['S', 'M', 'C', 'S', 'T', 'P'],
                     vec!['A', 'O', 'E', 'M', 'O', 'R'],
@@ -2456,7 +4007,8 @@ mod backtracking {
                     vec!['D', 'I', 'H', 'P', 'R', 'P'],
                     vec!['M', 'Y', 'Z', 'V', 'O', 'H'],
                     vec!['O', 'R', 'P', 'H', 'M', 'I'],
-                ]),
+                ])
+                .unwrap(),
                 "MORPHIUS"
             ))
         }
@@ -3686,6 +5238,80 @@ mod heap {
             assert_eq!(2, min_set_size(&[3, 3, 3, 3, 5, 5, 5, 2, 2, 7]));
         }
     }
+
+    mod kway_merge {
+        use super::super::super::heap::{kway_merge, kway_merge_dedup};
+        use super::super::random_data;
+
+        #[test]
+        fn it_merges_sorted_sources() {
+            let sources = (0..7)
+                .map(|seed| {
+                    let mut source = random_data(seed, seed as usize * 9, 40);
+                    source.sort();
+                    source
+                })
+                .collect::<Vec<_>>();
+            let mut expected = sources.concat();
+            expected.sort();
+            let merged = kway_merge(sources.clone());
+            assert_eq!(expected.len(), merged.size_hint().0);
+            assert_eq!(expected, merged.collect::<Vec<_>>());
+            assert_eq!(
+                expected,
+                kway_merge(sources.iter().map(|s| s.iter().copied())).collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn it_handles_empty_input() {
+            assert_eq!(None, kway_merge(Vec::<Vec<u32>>::new()).next());
+            assert_eq!(None, kway_merge(vec![vec![], Vec::<u32>::new()]).next());
+            assert_eq!(
+                vec![1, 2],
+                kway_merge(vec![vec![], vec![1, 2], vec![]]).collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn equal_items_keep_source_order() {
+            // ordered by key alone, so ties can only be told apart by the tag
+            #[derive(Debug)]
+            struct Keyed(u32, char);
+            impl PartialEq for Keyed {
+                fn eq(&self, other: &Self) -> bool {
+                    self.0 == other.0
+                }
+            }
+            impl Eq for Keyed {}
+            impl PartialOrd for Keyed {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+            impl Ord for Keyed {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.0.cmp(&other.0)
+                }
+            }
+            let sources = vec![
+                vec![Keyed(1, 'c'), Keyed(2, 'a')],
+                vec![Keyed(1, 'b'), Keyed(2, 'c')],
+                vec![Keyed(1, 'a')],
+            ];
+            let tags = |items: Vec<Keyed>| items.iter().map(|k| k.1).collect::<String>();
+            assert_eq!("cbaac", tags(kway_merge(sources).collect::<Vec<_>>()));
+            let sources = vec![vec![Keyed(1, 'x')], vec![Keyed(1, 'y'), Keyed(4, 'z')]];
+            assert_eq!("xz", tags(kway_merge_dedup(sources).collect::<Vec<_>>()));
+        }
+
+        #[test]
+        fn dedup_drops_repeats_within_and_across_sources() {
+            let merged = kway_merge_dedup(vec![vec![1, 1, 3, 5], vec![1, 2, 3], vec![5, 5, 6]])
+                .collect::<Vec<_>>();
+            assert_eq!(vec![1, 2, 3, 5, 6], merged);
+        }
+    }
 }
 
 mod graph {
@@ -3994,3 +5620,1464 @@ mod dynamic {
         }
     }
 }
+
+mod search_strategy {
+    use super::super::print_2d::Vec2d;
+    use super::super::search_strategy::*;
+
+    fn strategies() -> Vec<Box<SliceSearch<u32>>> {
+        vec![
+            Box::new(Linear),
+            Box::new(Binary),
+            Box::new(RecursiveBinary),
+            Box::new(RotatedBinary),
+            Box::new(Exponential),
+            Box::new(Interpolation),
+            Box::new(Jump),
+            Box::new(Fibonacci),
+        ]
+    }
+
+    #[test]
+    fn every_slice_strategy_agrees_on_sorted_input_chosen_at_runtime() {
+        let data = [1, 3, 3, 3, 7, 9, 12, 12, 40];
+        for strategy in strategies() {
+            assert_eq!(Some(4), strategy.find(&7, &data));
+            assert_eq!(None, strategy.find(&8, &data));
+            assert_eq!(vec![1, 2, 3], strategy.find_all(&3, &data));
+            assert_eq!(vec![6, 7], strategy.find_all(&12, &data));
+            assert_eq!(Vec::<usize>::new(), strategy.find_all(&0, &data));
+            assert!(strategy.contains(&40, &data));
+            assert!(!strategy.contains(&41, &data));
+            assert_eq!(None, strategy.find(&7, &[]));
+        }
+    }
+
+    #[test]
+    fn linear_find_all_works_on_unsorted_input() {
+        assert_eq!(vec![0, 3, 5], Linear.find_all(&4, &[4, 1, 2, 4, 9, 4]));
+    }
+
+    #[test]
+    fn rotated_find_all_collects_a_run_that_wraps_around() {
+        assert_eq!(vec![0, 4], RotatedBinary.find_all(&9, &[9, 2, 5, 7, 9]));
+        assert_eq!(vec![5], RotatedBinary.find_all(&5, &[6, 7, 8, 1, 2, 5]));
+    }
+
+    #[test]
+    fn staircase_finds_positions_in_a_sorted_grid() {
+        let grid = Vec2d::try_from(vec![
+            vec![1, 2, 4, 4],  //
+            vec![2, 4, 4, 8],  //
+            vec![4, 5, 9, 10], //
+        ])
+        .unwrap();
+        assert_eq!(Some((1, 0)), Staircase.find(&2, &grid));
+        assert_eq!(None, Staircase.find(&3, &grid));
+        assert!(Staircase.contains(&10, &grid));
+        assert_eq!(
+            vec![(2, 0), (3, 0), (1, 1), (2, 1), (0, 2)],
+            Staircase.find_all(&4, &grid)
+        );
+        assert_eq!(
+            None,
+            Staircase.find(&1, &Vec2d::try_from(Vec::<Vec<u32>>::new()).unwrap())
+        );
+    }
+
+    #[test]
+    fn staircase_searches_grids_of_owned_values() {
+        let grid = Vec2d::try_from(vec![
+            vec![String::from("ant"), String::from("cat")], //
+            vec![String::from("bee"), String::from("dog")], //
+        ])
+        .unwrap();
+        let cat = String::from("cat");
+        assert_eq!(Some((1, 0)), Staircase.find(&cat, &grid));
+        assert_eq!(vec![(1, 0)], Staircase.find_all(&cat, &grid));
+        assert!(!Staircase.contains(&String::from("cow"), &grid));
+    }
+}
+
+mod radix {
+    use super::super::radix::*;
+    use super::random_data;
+
+    #[test]
+    fn counting_sort_by_key_is_stable() {
+        let mut data = vec![(3, 'a'), (1, 'b'), (3, 'c'), (0, 'd'), (1, 'e')];
+        counting_sort_by_key(&mut data, 4, |(key, _)| *key);
+        assert_eq!(vec![(0, 'd'), (1, 'b'), (1, 'e'), (3, 'a'), (3, 'c')], data);
+    }
+
+    #[test]
+    fn lsd_and_msd_sort_random_unsigned_integers() {
+        for seed in 0..20 {
+            let data = random_data(seed, 500, u32::MAX - 1);
+            let mut expected = data.clone();
+            expected.sort();
+            let mut lsd = data.clone();
+            lsd_radix_sort(&mut lsd);
+            assert_eq!(expected, lsd);
+            let mut msd = data;
+            msd_radix_sort(&mut msd);
+            assert_eq!(expected, msd);
+        }
+    }
+
+    #[test]
+    fn lsd_and_msd_sort_signed_and_wide_integers() {
+        let signed = vec![5_i64, -3, i64::MIN, 0, i64::MAX, -1, 42, -42];
+        let mut expected = signed.clone();
+        expected.sort();
+        let mut lsd = signed.clone();
+        lsd_radix_sort(&mut lsd);
+        assert_eq!(expected, lsd);
+        let mut msd = signed;
+        msd_radix_sort(&mut msd);
+        assert_eq!(expected, msd);
+
+        let wide = (0..100_u128)
+            .map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835) ^ (i << 120))
+            .collect::<Vec<_>>();
+        let mut expected = wide.clone();
+        expected.sort();
+        let mut lsd = wide.clone();
+        lsd_radix_sort(&mut lsd);
+        assert_eq!(expected, lsd);
+        let mut msd = wide;
+        msd_radix_sort(&mut msd);
+        assert_eq!(expected, msd);
+
+        let mut tiny = vec![-128_i8, 127, 0, -1, 1];
+        lsd_radix_sort(&mut tiny);
+        assert_eq!(vec![-128_i8, -1, 0, 1, 127], tiny);
+    }
+
+    #[test]
+    fn lsd_and_msd_sort_strings_of_different_lengths() {
+        let words = "the quick brown fox jumps over the lazy dog then a theory of thermal \
+                     brownies quickly quiets a quirky fox in the dark "
+            .repeat(3)
+            .split(' ')
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut expected = words.clone();
+        expected.sort();
+        let mut lsd = words.clone();
+        lsd_radix_sort(&mut lsd);
+        assert_eq!(expected, lsd);
+        let mut msd = words;
+        msd_radix_sort(&mut msd);
+        assert_eq!(expected, msd);
+
+        let mut bytes = vec![
+            b"ab".to_vec(),
+            b"".to_vec(),
+            b"a".to_vec(),
+            b"\xff".to_vec(),
+        ];
+        msd_radix_sort(&mut bytes);
+        assert_eq!(
+            vec![
+                b"".to_vec(),
+                b"a".to_vec(),
+                b"ab".to_vec(),
+                b"\xff".to_vec()
+            ],
+            bytes
+        );
+    }
+
+    #[test]
+    fn keyed_records_are_sorted_stably() {
+        let records = random_data(11, 300, 20)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, key)| (key, format!("record {idx}")))
+            .collect::<Vec<_>>();
+        let mut expected = records.clone();
+        expected.sort_by_key(|(key, _)| *key);
+        let mut lsd = records.clone();
+        lsd_radix_sort_by_key(&mut lsd, |(key, _)| *key);
+        assert_eq!(expected, lsd);
+        let mut msd = records;
+        msd_radix_sort_by_key(&mut msd, |(key, _)| *key);
+        assert_eq!(expected, msd);
+    }
+}
+
+mod instrument {
+    use super::super::divide_conquer::sort::{MergeStrategy, QuickSortConfig};
+    use super::super::instrument::*;
+    use super::random_data;
+
+    #[test]
+    fn bubble_sort_counts_every_comparison_and_swap_on_reversed_input() {
+        let mut data = [5, 4, 3, 2, 1];
+        let stats = bubble_sort(&mut data);
+        assert_eq!([1, 2, 3, 4, 5], data);
+        assert_eq!(
+            Stats {
+                comparisons: 10,
+                swaps: 10,
+                moves: 0,
+                max_depth: 0
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn insertion_sort_does_a_single_pass_over_sorted_input() {
+        let mut data = (0..100).collect::<Vec<_>>();
+        let stats = insertion_sort(&mut data);
+        assert_eq!(99, stats.comparisons);
+        assert_eq!(0, stats.swaps);
+    }
+
+    #[test]
+    fn selection_sort_always_does_a_quadratic_number_of_comparisons() {
+        let mut data = random_data(4, 50, 100);
+        let stats = selection_sort(&mut data);
+        assert_eq!(50 * 49 / 2, stats.comparisons);
+        assert!(stats.swaps < 50);
+        assert!(data.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn merge_sort_records_moves_and_recursion_depth() {
+        for strategy in [
+            MergeStrategy::TopDown,
+            MergeStrategy::BottomUp,
+            MergeStrategy::InPlace,
+        ] {
+            let mut data = random_data(9, 64, 1000);
+            let mut expected = data.clone();
+            expected.sort();
+            let stats = merge_sort_with(&mut data, strategy);
+            assert_eq!(expected, data);
+            assert!(stats.comparisons > 0 && stats.comparisons <= 64 * 6 * 2);
+            assert!(stats.moves + stats.swaps > 0);
+            let expected_depth = if strategy == MergeStrategy::BottomUp {
+                0
+            } else {
+                6
+            };
+            assert_eq!(expected_depth, stats.max_depth, "{strategy:?}");
+        }
+    }
+
+    #[test]
+    fn quick_sort_stays_within_its_depth_limit() {
+        let mut data = random_data(2, 1024, 1_000_000);
+        let stats = quick_sort_with(&mut data, QuickSortConfig::default());
+        assert!(data.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(stats.max_depth <= 2 * 10 + 1);
+        assert!(stats.swaps > 0);
+        assert_eq!(stats, quick_sort(&mut random_data(2, 1024, 1_000_000)));
+    }
+
+    #[test]
+    fn searches_count_their_probes() {
+        let data = (0..1000).collect::<Vec<_>>();
+        let (found, stats) = binary_search(&777, &data);
+        assert_eq!(Some(777), found);
+        assert!(stats.comparisons <= 2 * 10);
+        // a miss at a probe costs both the equality and the ordering test
+        assert_eq!(3, binary_search(&3, &[1, 2, 3]).1.comparisons);
+        assert_eq!(1, binary_search(&2, &[1, 2, 3]).1.comparisons);
+
+        let (found, stats) = recursive_binary_search(&1000, &data);
+        assert_eq!(None, found);
+        assert!(stats.comparisons <= 10);
+        assert_eq!(stats.comparisons as usize, stats.max_depth);
+
+        let rotated = (500..1000).chain(0..500).collect::<Vec<_>>();
+        let (found, stats) = rotated_binary_search(&250, &rotated);
+        assert_eq!(Some(750), found);
+        assert!(stats.comparisons <= 2 * 10);
+    }
+
+    #[test]
+    fn shell_and_tim_sort_report_their_work() {
+        let mut data = [5, 4, 3, 2, 1];
+        let stats = shell_sort(&mut data);
+        assert_eq!([1, 2, 3, 4, 5], data);
+        // the gap of 4 swaps 5 and 1 first, saving bubble sort's 10 swaps down to 4
+        assert_eq!(4, stats.swaps);
+        assert_eq!(8, stats.comparisons);
+
+        let mut data = random_data(6, 500, 1000);
+        let stats = shell_sort_with(&mut data, crate::GapSequence::Knuth);
+        assert!(data.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(stats.comparisons >= stats.swaps && stats.swaps > 0);
+
+        let mut data = (0..200).collect::<Vec<_>>();
+        let stats = tim_sort(&mut data);
+        assert_eq!(199, stats.comparisons);
+        assert_eq!(0, stats.swaps + stats.moves);
+
+        let mut data = (0..200).rev().collect::<Vec<_>>();
+        let stats = tim_sort(&mut data);
+        assert!(data.iter().copied().eq(0..200));
+        assert_eq!(199, stats.comparisons);
+        assert_eq!(100, stats.swaps);
+
+        let mut data = random_data(8, 300, 1000);
+        let stats = tim_sort(&mut data);
+        assert!(data.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(stats.comparisons > 0 && stats.moves + stats.swaps > 0);
+    }
+
+    #[test]
+    fn distribution_sorts_count_their_range_scan_and_writes() {
+        let mut data = [3_usize, 1, 2, 1];
+        let stats = counting_sort(&mut data);
+        assert_eq!([1, 1, 2, 3], data);
+        assert_eq!(4, stats.moves);
+        assert_eq!(5, stats.comparisons);
+        assert_eq!(0, stats.swaps);
+
+        let mut data = [0.5_f32, 0.25, 1.0, 0.75];
+        let stats = bucket_sort(&mut data);
+        assert_eq!([0.25, 0.5, 0.75, 1.0], data);
+        assert_eq!(4, stats.moves);
+        assert!(stats.comparisons >= 3);
+    }
+}
+
+mod trace {
+    use super::super::divide_conquer::sort::{MergeStrategy, PivotStrategy, QuickSortConfig};
+    use super::super::trace::*;
+    use super::random_data;
+
+    fn assert_replay_matches(initial: &[u32], sorted: &[u32], trace: SortTrace<u32>) {
+        let mut replayed = initial.to_vec();
+        for event in trace {
+            if let SortEvent::Compare(a, b) = event {
+                assert!(a < initial.len() && b < initial.len());
+            }
+            event.apply(&mut replayed);
+        }
+        let mut expected = initial.to_vec();
+        expected.sort();
+        assert_eq!(expected, sorted);
+        assert_eq!(expected, replayed);
+    }
+
+    #[test]
+    fn replaying_the_simple_sorts_reproduces_the_sorted_result() {
+        for seed in 0..10 {
+            let initial = random_data(seed, 40, 20);
+            for sort in [bubble_sort, insertion_sort, selection_sort] {
+                let mut data = initial.clone();
+                let trace = sort(&mut data);
+                assert_replay_matches(&initial, &data, trace);
+            }
+        }
+    }
+
+    #[test]
+    fn replaying_merge_sort_reproduces_the_sorted_result() {
+        for strategy in [
+            MergeStrategy::TopDown,
+            MergeStrategy::BottomUp,
+            MergeStrategy::InPlace,
+        ] {
+            for seed in 0..10 {
+                let initial = random_data(seed, 77, 50);
+                let mut data = initial.clone();
+                let trace = merge_sort_with(&mut data, strategy);
+                assert_replay_matches(&initial, &data, trace);
+            }
+        }
+    }
+
+    #[test]
+    fn replaying_quick_sort_reproduces_the_sorted_result() {
+        for pivot in [
+            PivotStrategy::Last,
+            PivotStrategy::Ninther,
+            PivotStrategy::Random { seed: 3 },
+        ] {
+            for three_way in [false, true] {
+                let config = QuickSortConfig {
+                    pivot,
+                    three_way,
+                    insertion_cutoff: 4,
+                };
+                for seed in 0..10 {
+                    let initial = random_data(seed, 200, 30);
+                    let mut data = initial.clone();
+                    let trace = quick_sort_with(&mut data, config);
+                    assert_replay_matches(&initial, &data, trace);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn replaying_the_comparator_sorts_reproduces_the_sorted_result() {
+        for seed in 0..10 {
+            let initial = random_data(seed, 60, 25);
+            let mut expected = initial.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+            for sort in [merge_sort_by, quick_sort_by] {
+                let mut data = initial.clone();
+                let mut replayed = initial.clone();
+                for event in sort(&mut data, |a: &u32, b: &u32| b.cmp(a)) {
+                    event.apply(&mut replayed);
+                }
+                assert_eq!(expected, data);
+                assert_eq!(expected, replayed);
+            }
+        }
+    }
+
+    #[test]
+    fn quick_sort_reports_finished_partitions() {
+        let mut data = [3, 1, 2, 5, 4];
+        let trace = quick_sort_with(
+            &mut data,
+            QuickSortConfig {
+                pivot: PivotStrategy::Last,
+                three_way: false,
+                insertion_cutoff: 0,
+            },
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                SortEvent::Compare(0, 4),
+                SortEvent::Compare(1, 4),
+                SortEvent::Compare(2, 4),
+                SortEvent::Compare(3, 4),
+                SortEvent::Swap(3, 4),
+                SortEvent::PartitionDone(3..4),
+            ],
+            trace[..6]
+        );
+        assert_eq!([1, 2, 3, 4, 5], data);
+    }
+
+    #[test]
+    fn bubble_sort_emits_the_expected_event_sequence() {
+        let mut data = [2, 1, 3];
+        let trace = bubble_sort(&mut data).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                SortEvent::Compare(0, 1),
+                SortEvent::Swap(0, 1),
+                SortEvent::Compare(1, 2),
+                SortEvent::Compare(0, 1),
+            ],
+            trace
+        );
+    }
+}
+
+mod external {
+    use std::{fs, io::Cursor, path::PathBuf};
+
+    use super::super::external::*;
+    use super::random_data;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ex11-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn sorts_lines_across_many_runs_and_cleans_up() {
+        let dir = scratch_dir("lines");
+        let numbers = random_data(3, 1000, 500);
+        let input = numbers
+            .iter()
+            .map(|n| format!("{n:03}\n"))
+            .collect::<String>();
+        let mut expected = numbers
+            .iter()
+            .map(|n| format!("{n:03}"))
+            .collect::<Vec<_>>();
+        expected.sort();
+        let config = ExternalSortConfig {
+            chunk_len: 37,
+            temp_dir: dir.clone(),
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        external_sort(&LineCodec, input.as_bytes(), &mut output, &config).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(expected, output.lines().collect::<Vec<_>>());
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn merges_more_runs_than_the_fan_in_over_several_passes() {
+        let dir = scratch_dir("passes");
+        let numbers = random_data(12, 500, 10_000);
+        let input = numbers
+            .iter()
+            .map(|n| format!("{n:05}\n"))
+            .collect::<String>();
+        let mut expected = numbers
+            .iter()
+            .map(|n| format!("{n:05}"))
+            .collect::<Vec<_>>();
+        expected.sort();
+        for max_fan_in in [0, 2, 3, 7, 500] {
+            let config = ExternalSortConfig {
+                chunk_len: 4,
+                max_fan_in,
+                temp_dir: dir.clone(),
+            };
+            let mut output = Vec::new();
+            external_sort(&LineCodec, input.as_bytes(), &mut output, &config).unwrap();
+            let output = String::from_utf8(output).unwrap();
+            assert_eq!(expected, output.lines().collect::<Vec<_>>(), "{max_fan_in}");
+            assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        }
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sorts_in_memory_when_input_fits_in_one_chunk() {
+        let config = ExternalSortConfig {
+            chunk_len: 10,
+            temp_dir: PathBuf::from("/nonexistent/ex11"),
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        external_sort(&LineCodec, "b\r\nc\na".as_bytes(), &mut output, &config).unwrap();
+        assert_eq!(b"a\nb\nc\n".to_vec(), output);
+
+        let mut output = Vec::new();
+        external_sort(&LineCodec, "".as_bytes(), &mut output, &config).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn sorts_fixed_width_records() {
+        let dir = scratch_dir("fixed");
+        let records = random_data(8, 300, u32::MAX)
+            .into_iter()
+            .map(u32::to_be_bytes)
+            .collect::<Vec<_>>();
+        let mut expected = records.clone();
+        expected.sort();
+        let config = ExternalSortConfig {
+            chunk_len: 16,
+            temp_dir: dir.clone(),
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        external_sort(
+            &FixedWidthCodec { width: 4 },
+            Cursor::new(records.concat()),
+            &mut output,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(expected.concat(), output);
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_trailing_partial_record_and_cleans_up() {
+        let dir = scratch_dir("partial");
+        let config = ExternalSortConfig {
+            chunk_len: 2,
+            temp_dir: dir.clone(),
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        let err = external_sort(
+            &FixedWidthCodec { width: 3 },
+            &b"abcdefghijklm"[..],
+            &mut output,
+            &config,
+        )
+        .unwrap_err();
+        assert_eq!(std::io::ErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_unusable_temp_dir() {
+        let config = ExternalSortConfig {
+            chunk_len: 1,
+            temp_dir: PathBuf::from("/nonexistent/ex11"),
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        assert!(external_sort(&LineCodec, "b\na\n".as_bytes(), &mut output, &config).is_err());
+    }
+}
+
+mod selection {
+    use super::super::selection::*;
+    use super::random_data;
+
+    #[test]
+    fn select_nth_matches_sorted_position() {
+        for seed in 0..20 {
+            let input = random_data(seed, seed as usize * 13, 60);
+            let mut sorted = input.clone();
+            sorted.sort();
+            for k in 0..input.len() {
+                let mut data = input.clone();
+                assert_eq!(Some(&sorted[k]), select_nth(&mut data, k));
+                assert!(data[..k].iter().all(|item| *item <= data[k]));
+                assert!(data[k..].iter().all(|item| *item >= data[k]));
+            }
+            let mut data = input.clone();
+            assert_eq!(None, select_nth(&mut data, input.len()));
+        }
+    }
+
+    #[test]
+    fn select_nth_survives_adversarial_input() {
+        // all equal, presorted, descending and organ-pipe shapes
+        let len = 5000;
+        let inputs = [
+            vec![7; len],
+            (0..len as u32).collect::<Vec<_>>(),
+            (0..len as u32).rev().collect(),
+            (0..len as u32).map(|i| i.min(len as u32 - i)).collect(),
+        ];
+        for input in inputs {
+            let mut sorted = input.clone();
+            sorted.sort();
+            for k in [0, 1, len / 3, len / 2, len - 1] {
+                let mut data = input.clone();
+                assert_eq!(Some(&sorted[k]), select_nth(&mut data, k));
+            }
+        }
+    }
+
+    #[test]
+    fn select_nth_by_key_selects_on_a_field() {
+        let mut input = vec![("c", 3), ("a", 1), ("e", 5), ("b", 2), ("d", 4)];
+        assert_eq!(
+            Some(&("b", 2)),
+            select_nth_by_key(&mut input, 1, |(_, n)| *n)
+        );
+        assert_eq!(
+            Some(&("d", 4)),
+            select_nth_by(&mut input, 1, |a, b| b.1.cmp(&a.1))
+        );
+    }
+
+    #[test]
+    fn median_picks_the_lower_middle() {
+        assert_eq!(None, median::<u32>(&mut []));
+        assert_eq!(Some(&4), median(&mut [9, 4, 1]));
+        assert_eq!(Some(&3), median(&mut [9, 4, 1, 3]));
+    }
+
+    #[test]
+    fn top_k_is_sorted() {
+        let input = random_data(5, 200, 1000);
+        let mut sorted = input.clone();
+        sorted.sort();
+        for k in [0, 1, 10, 200, 300] {
+            let smallest = sorted.iter().take(k).copied().collect::<Vec<_>>();
+            let largest = sorted.iter().rev().take(k).copied().collect::<Vec<_>>();
+            assert_eq!(smallest, top_k_smallest(&input, k));
+            assert_eq!(largest, top_k_largest(&input, k));
+            assert_eq!(smallest, top_k_smallest_iter(input.iter().copied(), k));
+            assert_eq!(largest, top_k_largest_iter(input.iter().copied(), k));
+        }
+    }
+
+    #[test]
+    fn top_k_streams_without_collecting() {
+        let stream = || (1..=100_000_u64).map(|n| n.wrapping_mul(2_654_435_761) % 1_000_003);
+        let mut expected = stream().collect::<Vec<_>>();
+        expected.sort();
+        expected.truncate(3);
+        let mut top = TopK::new(3);
+        assert!(top.is_empty());
+        top.extend(stream());
+        assert_eq!(3, top.len());
+        assert_eq!(expected, top.into_sorted_vec());
+    }
+}
+
+mod max_subarray {
+    use super::super::max_subarray::*;
+    use super::random_data;
+
+    fn signed_data(seed: u64, len: usize) -> Vec<i64> {
+        random_data(seed, len, 20)
+            .into_iter()
+            .map(|n| n as i64 - 10)
+            .collect()
+    }
+
+    fn brute_force(items: &[i64], min_len: usize, max_len: usize) -> Option<i64> {
+        (0..items.len())
+            .flat_map(|start| (start + 1..=items.len()).map(move |end| start..end))
+            .filter(|range| range.len() >= min_len && range.len() <= max_len)
+            .map(|range| items[range].iter().sum())
+            .max()
+    }
+
+    #[test]
+    fn kadane_reports_sum_and_bounds() {
+        assert_eq!(None, kadane::<i32>(&[]));
+        assert_eq!(
+            Some(MaxSubarray {
+                sum: 6,
+                range: 3..7
+            }),
+            kadane(&[-2, 1, -3, 4, -1, 2, 1, -5, 4])
+        );
+        assert_eq!(
+            Some(MaxSubarray {
+                sum: -1,
+                range: 1..2
+            }),
+            kadane(&[-3, -1, -2])
+        );
+        for seed in 0..30 {
+            let items = signed_data(seed, seed as usize + 1);
+            let result = kadane(&items).unwrap();
+            assert_eq!(brute_force(&items, 1, items.len()), Some(result.sum));
+            assert_eq!(result.sum, items[result.range].iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn circular_may_wrap_around() {
+        assert_eq!(
+            Some(CircularSubarray {
+                sum: 22,
+                range: 6..7,
+                wrapped: 0..5
+            }),
+            circular(&[8, -8, 9, -9, 10, -11, 12])
+        );
+        assert_eq!(
+            Some(CircularSubarray {
+                sum: 12,
+                range: 4..6,
+                wrapped: 0..3
+            }),
+            circular(&[5, -3, 5, -20, 1, 4])
+        );
+        assert_eq!(
+            Some(CircularSubarray {
+                sum: 7,
+                range: 1..3,
+                wrapped: 0..0
+            }),
+            circular(&[-1, 3, 4, -9])
+        );
+        for seed in 0..30 {
+            let items = signed_data(seed, seed as usize + 1);
+            let doubled = [items.clone(), items.clone()].concat();
+            let expected = (0..items.len())
+                .filter_map(|start| {
+                    brute_force(&doubled[start..start + items.len()], 1, items.len())
+                })
+                .max();
+            let result = circular(&items).unwrap();
+            assert_eq!(expected, Some(result.sum));
+            assert!(result.range.len() + result.wrapped.len() <= items.len());
+            assert!(result.wrapped.is_empty() || result.range.end == items.len());
+            let sum = items[result.range].iter().sum::<i64>();
+            assert_eq!(result.sum, sum + items[result.wrapped].iter().sum::<i64>());
+        }
+    }
+
+    #[test]
+    fn max_product_tracks_sign_changes() {
+        assert_eq!(None, max_product::<i32>(&[]));
+        assert_eq!(
+            Some(MaxProduct {
+                product: 6,
+                range: 0..2
+            }),
+            max_product(&[2, 3, -2, 4])
+        );
+        assert_eq!(
+            Some(MaxProduct {
+                product: 48,
+                range: 1..5
+            }),
+            max_product(&[-1, -2, 3, -4, 2, 0, 5])
+        );
+        assert_eq!(
+            Some(MaxProduct {
+                product: 0,
+                range: 0..2
+            }),
+            max_product(&[-2, 0, -1])
+        );
+        for seed in 0..30 {
+            let items = signed_data(seed, 8)
+                .into_iter()
+                .map(|n| n / 3)
+                .collect::<Vec<_>>();
+            let expected = (0..items.len())
+                .flat_map(|start| (start + 1..=items.len()).map(move |end| start..end))
+                .map(|range| items[range].iter().product::<i64>())
+                .max();
+            let result = max_product(&items).unwrap();
+            assert_eq!(expected, Some(result.product));
+            assert_eq!(result.product, items[result.range].iter().product::<i64>());
+        }
+    }
+
+    #[test]
+    fn with_length_respects_bounds() {
+        assert_eq!(None, with_length(&[1, 2, 3], 4, 5));
+        assert_eq!(None, with_length(&[1, 2, 3], 3, 2));
+        assert_eq!(
+            Some(MaxSubarray {
+                sum: 5,
+                range: 1..3
+            }),
+            with_length(&[1, 2, 3, -10], 0, 2)
+        );
+        for seed in 0..30 {
+            let items = signed_data(seed, 25);
+            for (min_len, max_len) in [(1, 1), (1, 25), (3, 5), (10, 30), (25, 25)] {
+                let result = with_length(&items, min_len, max_len).unwrap();
+                assert_eq!(brute_force(&items, min_len, max_len), Some(result.sum));
+                assert!(result.range.len() >= min_len && result.range.len() <= max_len);
+                assert_eq!(result.sum, items[result.range].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn k_non_overlapping_picks_disjoint_subarrays() {
+        assert_eq!(None, k_non_overlapping(&[1, 2], 3));
+        assert_eq!(Some(vec![]), k_non_overlapping(&[1, 2], 0));
+        assert_eq!(
+            Some(vec![
+                MaxSubarray {
+                    sum: 7,
+                    range: 0..2
+                },
+                MaxSubarray {
+                    sum: 9,
+                    range: 3..5
+                },
+            ]),
+            k_non_overlapping(&[3, 4, -10, 5, 4, -1], 2)
+        );
+        assert_eq!(
+            Some(vec![
+                MaxSubarray {
+                    sum: -1,
+                    range: 0..1
+                },
+                MaxSubarray {
+                    sum: -2,
+                    range: 1..2
+                },
+            ]),
+            k_non_overlapping(&[-1, -2, -3], 2)
+        );
+        for seed in 0..20 {
+            let items = signed_data(seed, 9);
+            for k in 1..=3 {
+                let result = k_non_overlapping(&items, k).unwrap();
+                assert_eq!(k, result.len());
+                assert!(result
+                    .windows(2)
+                    .all(|w| w[0].range.end <= w[1].range.start));
+                let total = result.iter().map(|s| s.sum).sum::<i64>();
+                for subarray in &result {
+                    assert_eq!(
+                        subarray.sum,
+                        items[subarray.range.clone()].iter().sum::<i64>()
+                    );
+                }
+                assert_eq!(best_split(&items, k), total);
+            }
+        }
+    }
+
+    // best total of exactly k disjoint non-empty subarrays, trying every placement
+    fn best_split(items: &[i64], k: usize) -> i64 {
+        if k == 0 {
+            return 0;
+        }
+        let mut best = i64::MIN;
+        for start in 0..items.len() {
+            for end in start + 1..=items.len() {
+                if items.len() - end >= k - 1 {
+                    let rest = best_split(&items[end..], k - 1);
+                    best = best.max(items[start..end].iter().sum::<i64>() + rest);
+                }
+            }
+        }
+        best
+    }
+}
+
+mod range_query {
+    use super::super::matrix::Matrix2d;
+    use super::super::range_query::*;
+    use super::random_data;
+
+    type Combine = fn(&u64, &u64) -> u64;
+    type CombineInPlace = fn(&mut u64, u64);
+
+    fn gcd(a: &u64, b: &u64) -> u64 {
+        let (mut a, mut b) = (*a, *b);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    }
+
+    #[test]
+    fn prefix_sums_answer_every_range() {
+        let items = random_data(4, 30, 100)
+            .into_iter()
+            .map(i64::from)
+            .collect::<Vec<_>>();
+        let sums = PrefixSums::new(&items);
+        assert_eq!(30, sums.len());
+        assert_eq!(items.iter().sum::<i64>(), sums.total());
+        for start in 0..=items.len() {
+            for end in start..=items.len() {
+                assert_eq!(items[start..end].iter().sum::<i64>(), sums.sum(start..end));
+            }
+        }
+        let empty = PrefixSums::<i64>::new(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(0, empty.sum(0..0));
+    }
+
+    #[test]
+    fn fenwick_tree_tracks_point_updates() {
+        let mut items = random_data(5, 50, 100)
+            .into_iter()
+            .map(i64::from)
+            .collect::<Vec<_>>();
+        let mut tree = FenwickTree::from(&items[..]);
+        let ops = random_data(6, 300, 1000);
+        for op in ops.chunks(3) {
+            let (idx, value) = (op[0] as usize % items.len(), op[1] as i64 - 500);
+            if op[2] % 2 == 0 {
+                tree.add(idx, value);
+                items[idx] += value;
+            } else {
+                tree.set(idx, value);
+                items[idx] = value;
+            }
+            let end = op[2] as usize % (items.len() + 1);
+            let start = end.min(op[0] as usize % 7);
+            assert_eq!(items[..end].iter().sum::<i64>(), tree.prefix_sum(end));
+            assert_eq!(items[start..end].iter().sum::<i64>(), tree.sum(start..end));
+            assert_eq!(items[idx], tree.get(idx));
+        }
+        let mut built = FenwickTree::new(items.len());
+        for (idx, item) in items.iter().enumerate() {
+            built.add(idx, *item);
+        }
+        assert_eq!(FenwickTree::from(&items[..]), built);
+    }
+
+    #[test]
+    fn segment_tree_supports_any_monoid_with_range_assignment() {
+        let items = random_data(7, 37, 60)
+            .into_iter()
+            .map(|n| u64::from(n) * 6)
+            .collect::<Vec<_>>();
+        let monoids: [(u64, Combine); 4] = [
+            (0, |a, b| a + b),
+            (u64::MAX, |a, b| *a.min(b)),
+            (0, |a, b| *a.max(b)),
+            (0, gcd),
+        ];
+        for (identity, combine) in monoids {
+            let mut expected = items.clone();
+            let mut tree = SegmentTree::new(&items, identity, combine);
+            let ops = random_data(8, 400, 1000);
+            for op in ops.chunks(4) {
+                let a = op[0] as usize % (items.len() + 1);
+                let b = op[1] as usize % (items.len() + 1);
+                let range = a.min(b)..a.max(b);
+                let value = u64::from(op[2]) * 6;
+                match op[3] % 3 {
+                    0 => {
+                        tree.assign(range.clone(), value);
+                        expected[range].fill(value);
+                    }
+                    1 if !range.is_empty() => {
+                        tree.set(range.start, value);
+                        expected[range.start] = value;
+                    }
+                    _ => {
+                        let naive = expected[range.clone()]
+                            .iter()
+                            .fold(identity, |acc, item| combine(&acc, item));
+                        assert_eq!(naive, tree.query(range));
+                    }
+                }
+            }
+            let naive = expected
+                .iter()
+                .fold(identity, |acc, item| combine(&acc, item));
+            assert_eq!(naive, tree.query(0..tree.len()));
+        }
+    }
+
+    #[test]
+    fn sparse_table_answers_idempotent_queries() {
+        let items = random_data(9, 45, 200)
+            .into_iter()
+            .map(|n| u64::from(n) * 4)
+            .collect::<Vec<_>>();
+        let combines: [(u64, CombineInPlace); 3] = [
+            (u64::MAX, |a, b| *a = (*a).min(b)),
+            (0, |a, b| *a = (*a).max(b)),
+            (0, |a, b| *a = gcd(a, &b)),
+        ];
+        for (identity, combine) in combines {
+            let table = SparseTable::new(&items, combine);
+            assert_eq!(items.len(), table.len());
+            assert_eq!(None, table.query(3..3));
+            for start in 0..items.len() {
+                for end in start + 1..=items.len() {
+                    let naive = items[start..end].iter().fold(identity, |mut acc, item| {
+                        combine(&mut acc, *item);
+                        acc
+                    });
+                    assert_eq!(Some(naive), table.query(start..end));
+                }
+            }
+        }
+        assert!(SparseTable::new(&[], |a: &mut u8, b| *a = (*a).min(b)).is_empty());
+    }
+
+    #[test]
+    fn sparse_table_2d_answers_submatrix_queries() {
+        let (width, height) = (6, 9);
+        let matrix = Matrix2d::try_from((width, random_data(10, width * height, 1000))).unwrap();
+        let min = SparseTable2d::new(&matrix, |a: &mut u32, b| *a = (*a).min(b));
+        let max = SparseTable2d::new(&matrix, |a: &mut u32, b| *a = (*a).max(b));
+        for col1 in 0..width {
+            for col2 in col1..width {
+                for row1 in 0..height {
+                    for row2 in row1..height {
+                        let cells = (row1..=row2)
+                            .flat_map(|row| (col1..=col2).map(move |col| (col, row)))
+                            .map(|cell| matrix[cell])
+                            .collect::<Vec<_>>();
+                        assert_eq!(cells.iter().min(), Some(&min.query(col1, row1, col2, row2)));
+                        assert_eq!(cells.iter().max(), Some(&max.query(col1, row1, col2, row2)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn segment_tree_handles_empty_and_single_item_input() {
+        let tree = SegmentTree::new(&[], 0, |a: &u32, b: &u32| a + b);
+        assert!(tree.is_empty());
+        assert_eq!(0, tree.query(0..0));
+        let mut tree = SegmentTree::new(&[5], 0, |a: &u32, b: &u32| a + b);
+        tree.assign(0..1, 9);
+        assert_eq!(9, tree.query(0..1));
+    }
+}
+
+mod two_pointers {
+    use super::super::two_pointers::*;
+    use super::random_data;
+
+    fn sorted_data(seed: u64, len: usize) -> Vec<i64> {
+        let mut items = random_data(seed, len, 30)
+            .into_iter()
+            .map(|n| n as i64 - 15)
+            .collect::<Vec<_>>();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn pair_sums_finds_every_distinct_pair() {
+        assert_eq!(
+            vec![(1, 9), (3, 7), (5, 5)],
+            pair_sums(&[1, 3, 3, 5, 5, 7, 9, 9], 10)
+        );
+        assert_eq!(Vec::<(u32, u32)>::new(), pair_sums(&[5], 10));
+        for seed in 0..20 {
+            let items = sorted_data(seed, 25);
+            for target in -10..10 {
+                let mut expected = (0..items.len())
+                    .flat_map(|i| (i + 1..items.len()).map(move |j| (i, j)))
+                    .map(|(i, j)| (items[i], items[j]))
+                    .filter(|(a, b)| a + b == target)
+                    .collect::<Vec<_>>();
+                expected.sort();
+                expected.dedup();
+                assert_eq!(expected, pair_sums(&items, target));
+            }
+        }
+    }
+
+    #[test]
+    fn closest_pair_sum_minimizes_the_distance() {
+        assert_eq!(Some((4, 7)), closest_pair_sum(&[1, 2, 4, 7, 20], 12));
+        assert_eq!(None, closest_pair_sum(&[1], 12));
+        for seed in 0..20 {
+            let items = sorted_data(seed, 12);
+            let best = (0..items.len())
+                .flat_map(|i| (i + 1..items.len()).map(move |j| (i, j)))
+                .map(|(i, j)| (items[i] + items[j] - 7).abs())
+                .min()
+                .unwrap();
+            let (a, b) = closest_pair_sum(&items, 7).unwrap();
+            assert_eq!(best, (a + b - 7).abs());
+        }
+    }
+
+    #[test]
+    fn three_and_four_sums_match_brute_force() {
+        for seed in 0..10 {
+            let items = sorted_data(seed, 14);
+            let n = items.len();
+            for target in [-5, 0, 3] {
+                let mut triplets = Vec::new();
+                let mut quadruplets = Vec::new();
+                for i in 0..n {
+                    for j in i + 1..n {
+                        for k in j + 1..n {
+                            if items[i] + items[j] + items[k] == target {
+                                triplets.push((items[i], items[j], items[k]));
+                            }
+                            for l in k + 1..n {
+                                if items[i] + items[j] + items[k] + items[l] == target {
+                                    quadruplets.push((items[i], items[j], items[k], items[l]));
+                                }
+                            }
+                        }
+                    }
+                }
+                triplets.sort();
+                triplets.dedup();
+                quadruplets.sort();
+                quadruplets.dedup();
+                assert_eq!(triplets, three_sums(&items, target));
+                assert_eq!(quadruplets, four_sums(&items, target));
+            }
+        }
+    }
+
+    #[test]
+    fn closest_three_sum_minimizes_the_distance() {
+        assert_eq!(Some((-1, 1, 2)), closest_three_sum(&[-4, -1, 1, 2], 1));
+        assert_eq!(None, closest_three_sum(&[1, 2], 1));
+        for seed in 0..10 {
+            let items = sorted_data(seed, 10);
+            let mut best = i64::MAX;
+            for i in 0..items.len() {
+                for j in i + 1..items.len() {
+                    for k in j + 1..items.len() {
+                        best = best.min((items[i] + items[j] + items[k] - 40).abs());
+                    }
+                }
+            }
+            let (a, b, c) = closest_three_sum(&items, 40).unwrap();
+            assert_eq!(best, (a + b + c - 40).abs());
+        }
+    }
+
+    #[test]
+    fn counting_matches_brute_force() {
+        assert_eq!(3, count_pairs_with_difference(&[1, 1, 3, 5], 2));
+        assert_eq!(1, count_pairs_with_difference(&[1, 1, 3, 5], 0));
+        assert_eq!(3, count_pairs_with_difference(&[1, 1, 3, 5], -2));
+        assert_eq!(0, count_pairs_with_difference(&[250_u8, 255], 10));
+        assert_eq!(1, count_pairs_with_difference(&[245_u8, 255], 10));
+        assert_eq!(2, count_pairs_with_difference(&[0, 127, 127_i8], 127));
+        assert_eq!(2, count_pairs_sum_less_than(&[1, 2, 3, 4], 5));
+        for seed in 0..20 {
+            let items = sorted_data(seed, 30);
+            let index_pairs =
+                || (0..items.len()).flat_map(|i| (i + 1..items.len()).map(move |j| (i, j)));
+            for k in [-3_i64, 0, 1, 4, 40] {
+                let expected = index_pairs()
+                    .filter(|&(i, j)| (items[j] - items[i]).abs() == k.abs())
+                    .count();
+                assert_eq!(expected, count_pairs_with_difference(&items, k));
+            }
+            for target in [-30, -1, 0, 7, 30] {
+                let expected = index_pairs()
+                    .filter(|&(i, j)| items[i] + items[j] < target)
+                    .count();
+                assert_eq!(expected, count_pairs_sum_less_than(&items, target));
+            }
+        }
+    }
+}
+
+mod grid {
+    use super::super::grid::{Connectivity, Grid, GridErrorKind};
+    use super::super::{matrix::Matrix2d, print_2d::Vec2d};
+
+    fn sample() -> Grid<u32> {
+        Grid::try_from(vec![
+            vec![1, 2, 3, 4],    //
+            vec![5, 6, 7, 8],    //
+            vec![9, 10, 11, 12], //
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = sample();
+        assert_eq!((4, 3, 12), (grid.width(), grid.height(), grid.size()));
+        assert_eq!(&[5, 6, 7, 8], grid.row(1));
+        assert_eq!(vec![3, 7, 11], grid.column(2).copied().collect::<Vec<_>>());
+        assert_eq!(
+            vec![11, 7, 3],
+            grid.column(2).rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(3, grid.column(0).len());
+        assert_eq!(3, grid.rows().len());
+        assert_eq!(
+            vec![4, 8, 12],
+            grid.columns().last().unwrap().copied().collect::<Vec<_>>()
+        );
+        let mut grid = grid;
+        for cell in grid.column_mut(1) {
+            *cell *= 10;
+        }
+        grid.row_mut(2)[3] = 0;
+        assert_eq!(&[9, 100, 11, 0], grid.row(2));
+    }
+
+    #[test]
+    fn works_without_copy() {
+        let mut grid = Grid::new(2, 2, String::from("a"));
+        grid[(1, 0)].push('b');
+        assert_eq!("ab", grid[(1, 0)]);
+        assert_eq!(
+            Grid::try_from((2, vec![1, 2, 1, 1])).unwrap(),
+            grid.map(|cell| cell.len())
+        );
+    }
+
+    #[test]
+    fn views_borrow_sub_grids() {
+        let grid = sample();
+        let view = grid.view(1..3, 1..3);
+        assert_eq!((2, 2, 4), (view.width(), view.height(), view.size()));
+        assert_eq!(&[10, 11], view.row(1));
+        assert_eq!(7, view[(1, 0)]);
+        assert_eq!(vec![6, 10], view.column(0).copied().collect::<Vec<_>>());
+        assert_eq!(
+            Grid::try_from(vec![vec![6, 7], vec![10, 11]]).unwrap(),
+            view.to_grid()
+        );
+        assert_eq!(
+            Grid::try_from(vec![vec![11]]).unwrap(),
+            view.view(1..2, 1..2).to_grid()
+        );
+        assert_eq!(0, grid.view(2..2, 0..3).size());
+        assert_eq!(grid, grid.as_view().to_grid());
+
+        let mut grid = grid;
+        let mut view = grid.view_mut(0..2, 1..3);
+        view.fill(0);
+        view[(1, 1)] = 42;
+        for cell in view.column_mut(0) {
+            *cell += 1;
+        }
+        assert_eq!(
+            vec![vec![1, 0], vec![1, 42]],
+            view.as_view()
+                .rows()
+                .map(<[u32]>::to_vec)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Grid::try_from(vec![
+                vec![1, 2, 3, 4],    //
+                vec![1, 0, 7, 8],    //
+                vec![1, 42, 11, 12]  //
+            ])
+            .unwrap(),
+            grid
+        );
+    }
+
+    #[test]
+    fn grids_without_rows_have_empty_columns() {
+        let grid = Grid::new(3, 0, 0);
+        assert_eq!((3, 0, 0), (grid.width(), grid.height(), grid.size()));
+        assert_eq!(3, grid.columns().count());
+        assert!(grid.columns().all(|mut column| column.next().is_none()));
+        assert_eq!(0, grid.column(2).count());
+        assert_eq!(0, grid.rows().count());
+
+        let transposed = grid.transpose();
+        let mut in_place = grid.clone();
+        in_place.transpose_in_place();
+        assert_eq!(transposed, in_place);
+        assert_eq!(0, transposed.size());
+
+        let mut grid = grid;
+        assert_eq!(0, grid.column_mut(1).count());
+    }
+
+    #[test]
+    fn empty_views_have_rows_and_columns_of_their_own_shape() {
+        let mut grid = sample();
+        let flat = grid.view(0..3, 1..1);
+        assert_eq!((3, 0), (flat.width(), flat.height()));
+        assert_eq!(0, flat.rows().count());
+        assert_eq!(3, flat.columns().count());
+        assert_eq!(0, flat.column(2).count());
+        assert_eq!(0, flat.to_grid().size());
+
+        let thin = grid.view(2..2, 0..3);
+        assert_eq!((0, 3), (thin.width(), thin.height()));
+        assert_eq!(0, thin.columns().count());
+        assert!(thin.row(2).is_empty());
+        assert_eq!(
+            vec![0; 3],
+            thin.rows().map(<[u32]>::len).collect::<Vec<_>>()
+        );
+
+        let mut flat = grid.view_mut(1..4, 3..3);
+        assert_eq!(0, flat.rows_mut().count());
+        assert_eq!(0, flat.column_mut(2).count());
+        flat.fill(0);
+
+        let mut thin = grid.view_mut(4..4, 1..3);
+        assert!(thin.row_mut(1).is_empty());
+        assert_eq!(
+            vec![0; 2],
+            thin.rows_mut().map(|row| row.len()).collect::<Vec<_>>()
+        );
+        thin.fill(0);
+        assert_eq!(sample(), grid);
+    }
+
+    #[test]
+    #[should_panic]
+    fn views_must_fit_inside_the_grid() {
+        sample().view(0..5, 0..1);
+    }
+
+    #[test]
+    fn map_zip_with_and_transpose() {
+        let grid = sample();
+        let doubled = grid.map(|cell| cell * 2);
+        assert_eq!(
+            Grid::try_from((4, (1..=12).map(|n| n * 3).collect::<Vec<_>>())).unwrap(),
+            grid.zip_with(&doubled, |a, b| a + b)
+        );
+        assert_eq!(
+            Grid::try_from(vec![
+                vec![1, 5, 9],  //
+                vec![2, 6, 10], //
+                vec![3, 7, 11], //
+                vec![4, 8, 12], //
+            ])
+            .unwrap(),
+            grid.transpose()
+        );
+        assert_eq!(grid, grid.transpose().transpose());
+        assert_eq!(
+            Grid::<u8>::try_from(vec![]).unwrap(),
+            Grid::try_from(vec![]).unwrap().transpose()
+        );
+    }
+
+    #[test]
+    fn construction_reports_malformed_input() {
+        let ragged = Grid::try_from(vec![vec![1, 2], vec![3], vec![4, 5]]).unwrap_err();
+        assert_eq!(
+            &GridErrorKind::RaggedRows {
+                row: 1,
+                expected: 2,
+                found: 1
+            },
+            ragged.kind()
+        );
+        assert_eq!("row 1 has 1 items, expected 2", ragged.to_string());
+        assert_eq!(
+            &GridErrorKind::RaggedRows {
+                row: 2,
+                expected: 3,
+                found: 1
+            },
+            Grid::try_from((3, vec![0; 7])).unwrap_err().kind()
+        );
+        assert_eq!(
+            &GridErrorKind::ZeroWidth,
+            Grid::<u8>::try_from(vec![vec![], vec![]])
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            &GridErrorKind::ZeroWidth,
+            Grid::try_from((0, vec![1])).unwrap_err().kind()
+        );
+        assert_eq!(
+            &GridErrorKind::SizeOverflow,
+            Grid::try_new(usize::MAX, 2, 0_u8).unwrap_err().kind()
+        );
+        assert_eq!(0, Grid::<u8>::try_from((0, vec![])).unwrap().size());
+    }
+
+    #[test]
+    fn get_is_bounds_checked() {
+        let mut grid = sample();
+        assert_eq!(Some(&8), grid.get(3, 1));
+        assert_eq!(None, grid.get(4, 1));
+        assert_eq!(None, grid.get(0, 3));
+        *grid.get_mut(0, 2).unwrap() = 0;
+        assert_eq!(0, grid[(0, 2)]);
+        assert_eq!(None, grid.get_mut(usize::MAX, 0));
+        assert_eq!(None, Grid::<u8>::try_from(vec![]).unwrap().get(0, 0));
+    }
+
+    #[test]
+    fn neighbors_clip_at_the_edges() {
+        let grid = sample();
+        assert_eq!(
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)],
+            grid.neighbors(1, 1, Connectivity::Four).collect::<Vec<_>>()
+        );
+        assert_eq!(8, grid.neighbors(1, 1, Connectivity::Eight).count());
+        assert_eq!(
+            vec![(1, 0), (0, 1)],
+            grid.neighbors(0, 0, Connectivity::Four).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(2, 1), (3, 1), (2, 2)],
+            grid.neighbors(3, 2, Connectivity::Eight)
+                .collect::<Vec<_>>()
+        );
+        let single = Grid::new(1, 1, 0);
+        assert_eq!(0, single.neighbors(0, 0, Connectivity::Eight).count());
+    }
+
+    #[test]
+    fn existing_names_refer_to_the_same_type() {
+        let vec2d: Vec2d<u32> = sample();
+        let matrix: Matrix2d<u32> = vec2d.clone();
+        assert_eq!(vec2d, matrix);
+    }
+}